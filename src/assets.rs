@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use crate::gltf_model::GltfModel;
+use crate::lod::LodMesh;
+use crate::obj::Obj;
+
+const ASSETS_DIR: &str = "assets";
+
+// Carga cada archivo de la carpeta de recursos una sola vez y reparte handles `Arc` a los
+// datos compartidos. Las rutas se indican relativas a `root`. Los modelos se centran y se
+// escalan a radio 1 al cargarse, así el tamaño con que se exportaron no importa.
+pub struct Assets {
+    root: PathBuf,
+    objs: HashMap<PathBuf, Arc<Obj>>,
+    gltf_models: HashMap<PathBuf, Arc<GltfModel>>,
+    lod_meshes: HashMap<PathBuf, LodMesh>,
+}
+
+impl Assets {
+    pub fn new(root: PathBuf) -> Self {
+        Assets { root, objs: HashMap::new(), gltf_models: HashMap::new(), lod_meshes: HashMap::new() }
+    }
+
+    // Busca la carpeta `assets` junto al ejecutable y en sus directorios padres (así también
+    // funciona desde `target/release`); si no aparece, se usa la del directorio actual
+    pub fn default_root() -> PathBuf {
+        std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.ancestors().skip(1).map(|dir| dir.join(ASSETS_DIR)).find(|dir| dir.is_dir()))
+            .unwrap_or_else(|| PathBuf::from(ASSETS_DIR))
+    }
+
+    pub fn path(&self, relative: &str) -> PathBuf {
+        self.root.join(relative)
+    }
+
+    pub fn obj(&mut self, relative: &str) -> Result<Arc<Obj>, String> {
+        let path = self.path(relative);
+        if let Some(obj) = self.objs.get(&path) {
+            return Ok(Arc::clone(obj));
+        }
+
+        let mut obj = Obj::load(&path.to_string_lossy()).map_err(|error| format!("{}: {}", path.display(), error))?;
+        obj.normalize();
+        let obj = Arc::new(obj);
+        self.objs.insert(path, Arc::clone(&obj));
+        Ok(obj)
+    }
+
+    pub fn gltf(&mut self, relative: &str) -> Result<Arc<GltfModel>, String> {
+        let path = self.path(relative);
+        if let Some(model) = self.gltf_models.get(&path) {
+            return Ok(Arc::clone(model));
+        }
+
+        let mut model = GltfModel::load(&path.to_string_lossy())?;
+        model.normalize();
+        let model = Arc::new(model);
+        self.gltf_models.insert(path, Arc::clone(&model));
+        Ok(model)
+    }
+
+    // Cadena de niveles de detalle de un OBJ. Las mallas se generan una vez y se comparten;
+    // cada copia devuelta elige su nivel por separado.
+    pub fn lod_mesh(&mut self, relative: &str) -> Result<LodMesh, String> {
+        let path = self.path(relative);
+        if let Some(lod) = self.lod_meshes.get(&path) {
+            return Ok(lod.clone());
+        }
+
+        let obj = self.obj(relative)?;
+        let lod = LodMesh::from_obj(&obj);
+        self.lod_meshes.insert(path, lod.clone());
+        Ok(lod)
+    }
+}
@@ -0,0 +1,130 @@
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+use crate::color::LinearColor;
+
+const VIEW_SAMPLES: usize = 16;
+const LIGHT_SAMPLES: usize = 6;
+
+// Parámetros de dispersión simple (Rayleigh + Mie) de la atmósfera de un cuerpo. Todas las
+// longitudes están en radios del planeta, así que los mismos valores sirven a cualquier
+// escala de la escena. El espesor está exagerado respecto del real para que se vea a la
+// distancia de la cámara; las alturas de escala y los coeficientes se ajustan igual, de
+// modo que la profundidad óptica total se conserva.
+#[derive(Debug, Clone, Copy)]
+pub struct Atmosphere {
+    pub thickness: f32,             // fracción del radio por encima de la superficie
+    pub rayleigh: Vec3,             // coeficientes de dispersión RGB por radio de planeta
+    pub rayleigh_scale_height: f32,
+    pub mie: Vec3,                  // polvo o bruma; puede tener color
+    pub mie_scale_height: f32,
+    pub mie_g: f32,                 // anisotropía de la función de fase de Mie
+    pub sun_intensity: f32,
+}
+
+impl Atmosphere {
+    // Luz dispersada hacia la cámara a lo largo del rayo `origin` + t·`direction` (en
+    // coordenadas de mundo). `planet_center` y `planet_radius` definen la superficie; el
+    // segmento termina donde el rayo sale de la atmósfera o choca con el planeta.
+    pub fn scatter(
+        &self,
+        origin: &Vec3,
+        direction: &Vec3,
+        planet_center: &Vec3,
+        planet_radius: f32,
+        light_position: &Vec3,
+    ) -> LinearColor {
+        // Trabajar en radios del planeta con el centro en el origen
+        let origin = (origin - planet_center) / planet_radius;
+        let light = (light_position - planet_center) / planet_radius;
+        let outer = 1.0 + self.thickness;
+
+        let (t_enter, t_exit) = match ray_sphere(&origin, direction, outer) {
+            Some((t0, t1)) if t1 > 0.0 => (t0.max(0.0), t1),
+            _ => return LinearColor::black(),
+        };
+        let t_exit = match ray_sphere(&origin, direction, 1.0) {
+            Some((t0, _)) if t0 > 0.0 => t_exit.min(t0),
+            _ => t_exit,
+        };
+
+        let step = (t_exit - t_enter) / VIEW_SAMPLES as f32;
+        let mut optical_rayleigh = 0.0;
+        let mut optical_mie = 0.0;
+        let mut sum_rayleigh = Vec3::zeros();
+        let mut sum_mie = Vec3::zeros();
+
+        for i in 0..VIEW_SAMPLES {
+            let point = origin + direction * (t_enter + step * (i as f32 + 0.5));
+            let height = point.magnitude() - 1.0;
+
+            let density_rayleigh = (-height / self.rayleigh_scale_height).exp() * step;
+            let density_mie = (-height / self.mie_scale_height).exp() * step;
+            optical_rayleigh += density_rayleigh;
+            optical_mie += density_mie;
+
+            let to_light = (light - point).normalize();
+
+            // Puntos a la sombra del planeta no reciben luz directa
+            let (light_rayleigh, light_mie) = match self.light_optical_depth(&point, &to_light, outer) {
+                Some(depths) => depths,
+                None => continue,
+            };
+
+            let tau = self.rayleigh * (optical_rayleigh + light_rayleigh)
+                + self.mie * 1.1 * (optical_mie + light_mie);
+            let attenuation = Vec3::new((-tau.x).exp(), (-tau.y).exp(), (-tau.z).exp());
+
+            let cos_theta = direction.dot(&to_light);
+            sum_rayleigh += attenuation * (density_rayleigh * phase_rayleigh(cos_theta));
+            sum_mie += attenuation * (density_mie * phase_mie(cos_theta, self.mie_g));
+        }
+
+        let color = (sum_rayleigh.component_mul(&self.rayleigh) + sum_mie.component_mul(&self.mie)) * self.sun_intensity;
+
+        LinearColor::new(color.x, color.y, color.z)
+    }
+
+    // Profundidad óptica desde `point` hasta el borde de la atmósfera en dirección a la luz
+    fn light_optical_depth(&self, point: &Vec3, to_light: &Vec3, outer: f32) -> Option<(f32, f32)> {
+        if let Some((t0, _)) = ray_sphere(point, to_light, 1.0) {
+            if t0 > 0.0 {
+                return None;
+            }
+        }
+
+        let (_, t_exit) = ray_sphere(point, to_light, outer)?;
+        let step = t_exit.max(0.0) / LIGHT_SAMPLES as f32;
+        let (mut rayleigh, mut mie) = (0.0, 0.0);
+
+        for i in 0..LIGHT_SAMPLES {
+            let sample = point + to_light * (step * (i as f32 + 0.5));
+            let height = sample.magnitude() - 1.0;
+            rayleigh += (-height / self.rayleigh_scale_height).exp() * step;
+            mie += (-height / self.mie_scale_height).exp() * step;
+        }
+
+        Some((rayleigh, mie))
+    }
+}
+
+fn phase_rayleigh(cos_theta: f32) -> f32 {
+    3.0 / (16.0 * PI) * (1.0 + cos_theta * cos_theta)
+}
+
+// Cornette-Shanks, una variante de Henyey-Greenstein con mejor forma para g alto
+fn phase_mie(cos_theta: f32, g: f32) -> f32 {
+    3.0 / (8.0 * PI) * ((1.0 - g * g) * (1.0 + cos_theta * cos_theta))
+        / ((2.0 + g * g) * (1.0 + g * g - 2.0 * g * cos_theta).powf(1.5))
+}
+
+// Intersecciones de un rayo con una esfera centrada en el origen (`direction` unitario)
+pub fn ray_sphere(origin: &Vec3, direction: &Vec3, radius: f32) -> Option<(f32, f32)> {
+    let b = origin.dot(direction);
+    let c = origin.dot(origin) - radius * radius;
+    let discriminant = b * b - c;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let root = discriminant.sqrt();
+    Some((-b - root, -b + root))
+}
@@ -18,6 +18,7 @@ impl Camera {
         }
     }
 
+    #[allow(dead_code)]
     pub fn basis_change(&self, vector: &Vec3) -> Vec3 {
         let forward = (self.center - self.eye).normalize();
         let right = forward.cross(&self.up).normalize();
@@ -74,6 +75,7 @@ impl Camera {
         self.has_changed = true;
     }
 
+    #[allow(dead_code)]
     pub fn check_if_changed(&mut self) -> bool {
         if self.has_changed {
             self.has_changed = false;
@@ -0,0 +1,124 @@
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+
+// Datos físicos incluidos en el binario, así el programa no depende del directorio actual
+const EMBEDDED_CATALOG: &str = include_str!("../assets/data/bodies.csv");
+
+const COLUMNS: usize = 8;
+
+// Identidad y datos físicos de un cuerpo del catálogo
+#[derive(Debug, Clone)]
+pub struct Body {
+    pub name: String,
+    pub parent: Option<String>,
+    pub radius_km: f32,
+    pub mass_kg: f64,
+    pub orbital_period_days: Option<f32>,
+    pub rotation_period_hours: f32, // negativo = retrógrado
+    pub axial_tilt_deg: f32,
+    pub albedo: Option<f32>,
+}
+
+impl Body {
+    // Rotación para `create_model_matrix` tras `days` días: el giro sobre el eje Y del modelo
+    // y luego la inclinación del eje sobre Z. El sentido retrógrado ya lo da una inclinación
+    // de más de 90°, por eso el giro usa el período sin signo. Los períodos más cortos que
+    // `min_period_days` se alargan para que el giro se pueda seguir con el reloj de la escena.
+    pub fn rotation(&self, days: f32, min_period_days: f32) -> Vec3 {
+        let period_days = (self.rotation_period_hours.abs() / 24.0).max(min_period_days);
+        let spin = if period_days > 0.0 { (2.0 * PI * days / period_days) % (2.0 * PI) } else { 0.0 };
+        Vec3::new(0.0, spin, self.axial_tilt_deg.to_radians())
+    }
+}
+
+pub struct Catalog {
+    bodies: Vec<Body>,
+}
+
+impl Catalog {
+    pub fn embedded() -> Self {
+        Catalog::parse(EMBEDDED_CATALOG).expect("catálogo de cuerpos incluido inválido")
+    }
+
+    // CSV con encabezado; las líneas vacías y las que empiezan con '#' se ignoran
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let (_, header) = lines.next().ok_or("catálogo vacío")?;
+        if header.split(',').count() != COLUMNS {
+            return Err(format!("se esperaban {} columnas en el encabezado", COLUMNS));
+        }
+
+        let bodies = lines
+            .map(|(number, line)| parse_body(line).map_err(|error| format!("línea {}: {}", number, error)))
+            .collect::<Result<Vec<Body>, String>>()?;
+
+        for body in &bodies {
+            if let Some(parent) = &body.parent {
+                if !bodies.iter().any(|other| &other.name == parent) {
+                    return Err(format!("{}: el padre '{}' no está en el catálogo", body.name, parent));
+                }
+            }
+        }
+
+        Ok(Catalog { bodies })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Body> {
+        self.bodies.iter().find(|body| body.name == name)
+    }
+
+    // Cuerpos que orbitan directamente alrededor de `name`, en el orden del catálogo
+    pub fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Body> + 'a {
+        self.bodies.iter().filter(move |body| body.parent.as_deref() == Some(name))
+    }
+
+    // Cuerpo que se sabe que está en el catálogo incluido
+    pub fn body(&self, name: &str) -> Body {
+        self.get(name).cloned().unwrap_or_else(|| panic!("'{}' no está en el catálogo", name))
+    }
+}
+
+fn parse_body(line: &str) -> Result<Body, String> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    if fields.len() != COLUMNS {
+        return Err(format!("se esperaban {} columnas y hay {}", COLUMNS, fields.len()));
+    }
+
+    Ok(Body {
+        name: fields[0].to_string(),
+        parent: optional(fields[1]).map(str::to_string),
+        radius_km: number(fields[2], "radio")?,
+        mass_kg: number(fields[3], "masa")?,
+        orbital_period_days: optional(fields[4]).map(|value| number(value, "período orbital")).transpose()?,
+        rotation_period_hours: number(fields[5], "período de rotación")?,
+        axial_tilt_deg: number(fields[6], "inclinación del eje")?,
+        albedo: optional(fields[7]).map(|value| number(value, "albedo")).transpose()?,
+    })
+}
+
+fn optional(field: &str) -> Option<&str> {
+    (field != "-").then_some(field)
+}
+
+fn number<T: std::str::FromStr>(field: &str, what: &str) -> Result<T, String> {
+    field.parse().map_err(|_| format!("valor inválido para {}: '{}'", what, field))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn children_follow_parents() {
+        let catalog = Catalog::embedded();
+        let moons: Vec<&str> = catalog.children("Tierra").map(|body| body.name.as_str()).collect();
+        assert_eq!(moons, ["Luna"]);
+        assert!(catalog.children("Sol").all(|body| body.parent.as_deref() == Some("Sol")));
+        assert_eq!(catalog.children("Luna").count(), 0);
+    }
+}
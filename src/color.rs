@@ -1,15 +1,40 @@
 use std::fmt;
+use std::ops::{AddAssign, Div, Sub};
+use std::sync::OnceLock;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Color {
     r: u8,
     g: u8,
     b: u8,
+    a: u8,
+}
+
+// Modos de mezcla de `Color::blend`. Los separables operan canal a canal en [0, 1] y el
+// resultado se combina con la base según el alfa del color de mezcla.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+pub enum BlendMode {
+    Normal, // el negro se trata como transparente
+    Multiply,
+    Add,
+    Subtract,
+    Screen,
+    Overlay,
+    SoftLight,
+    Darken,
+    Lighten,
+    Difference,
+    AlphaOver, // composición "over" de Porter-Duff usando el alfa de ambos colores
 }
 
 impl Color {
     pub const fn new(r: u8, g: u8, b: u8) -> Self {
-        Color { r, g, b }
+        Color { r, g, b, a: 255 }
+    }
+
+    pub const fn new_rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Color { r, g, b, a }
     }
 
     pub fn get_r(&self) -> u8 {
@@ -24,35 +49,78 @@ impl Color {
         self.b
     }
 
+    #[allow(dead_code)]
+    pub fn get_a(&self) -> u8 {
+        self.a
+    }
+
+    pub const fn with_alpha(self, a: u8) -> Self {
+        Color { a, ..self }
+    }
+
     pub const fn from_hex(hex: u32) -> Self {
         let r = ((hex >> 16) & 0xFF) as u8;
         let g = ((hex >> 8) & 0xFF) as u8;
         let b = (hex & 0xFF) as u8;
-        Color { r, g, b }
+        Color { r, g, b, a: 255 }
     }
 
     pub const fn black() -> Self {
-        Color { r: 0, g: 0, b: 0 }
+        Color { r: 0, g: 0, b: 0, a: 255 }
     }
 
-    pub fn to_hex(&self) -> u32 {
+    pub fn to_hex(self) -> u32 {
         ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
     }
 
-    // Linear interpolation between two colors
+    // Interpolación en espacio lineal, para que los tonos intermedios no se oscurezcan.
+    // El alfa se interpola directamente.
     pub fn lerp(&self, other: &Color, t: f32) -> Self {
         let t = t.clamp(0.0, 1.0);
-        Color {
-            r: (self.r as f32 + (other.r as f32 - self.r as f32) * t).round() as u8,
-            g: (self.g as f32 + (other.g as f32 - self.g as f32) * t).round() as u8,
-            b: (self.b as f32 + (other.b as f32 - self.b as f32) * t).round() as u8,
-        }
+        let a = (self.a as f32 + (other.a as f32 - self.a as f32) * t).round() as u8;
+        self.to_linear().lerp(&other.to_linear(), t).to_srgb().with_alpha(a)
     }
 
     pub fn is_black(&self) -> bool {
         self.r == 0 && self.g == 0 && self.b == 0 
     }
 
+    pub fn blend(&self, blend: &Color, mode: BlendMode) -> Color {
+        let separable = |f: fn(f32, f32) -> f32| {
+            let alpha = blend.a as f32 / 255.0;
+            let channel = |base: u8, top: u8| {
+                let (base, top) = (base as f32 / 255.0, top as f32 / 255.0);
+                let mixed = base + (f(base, top).clamp(0.0, 1.0) - base) * alpha;
+                (mixed * 255.0).round() as u8
+            };
+            Color::new_rgba(channel(self.r, blend.r), channel(self.g, blend.g), channel(self.b, blend.b), self.a)
+        };
+
+        match mode {
+            BlendMode::Normal => self.blend_normal(blend),
+            BlendMode::Multiply => self.blend_multiply(blend),
+            BlendMode::Add => self.blend_add(blend),
+            BlendMode::Subtract => self.blend_subtract(blend),
+            BlendMode::Screen => separable(|b, s| 1.0 - (1.0 - b) * (1.0 - s)),
+            BlendMode::Overlay => separable(|b, s| {
+                if b < 0.5 { 2.0 * b * s } else { 1.0 - 2.0 * (1.0 - b) * (1.0 - s) }
+            }),
+            // Fórmula de soft light del W3C (Compositing and Blending Level 1)
+            BlendMode::SoftLight => separable(|b, s| {
+                if s <= 0.5 {
+                    b - (1.0 - 2.0 * s) * b * (1.0 - b)
+                } else {
+                    let d = if b <= 0.25 { ((16.0 * b - 12.0) * b + 4.0) * b } else { b.sqrt() };
+                    b + (2.0 * s - 1.0) * (d - b)
+                }
+            }),
+            BlendMode::Darken => separable(f32::min),
+            BlendMode::Lighten => separable(f32::max),
+            BlendMode::Difference => separable(|b, s| (b - s).abs()),
+            BlendMode::AlphaOver => self.blend_over(blend),
+        }
+    }
+
     pub fn blend_normal(&self, blend: &Color) -> Color {
         if blend.is_black() { *self } else { *blend }
       }
@@ -74,12 +142,229 @@ impl Color {
     }
     
     pub fn blend_subtract(&self, blend: &Color) -> Color {
-        let r = (self.r as i16 - blend.r as i16).max(0).min(255) as u8;
-        let g = (self.g as i16 - blend.g as i16).max(0).min(255) as u8;
-        let b = (self.b as i16 - blend.b as i16).max(0).min(255) as u8;
+        let r = (self.r as i16 - blend.r as i16).clamp(0, 255) as u8;
+        let g = (self.g as i16 - blend.g as i16).clamp(0, 255) as u8;
+        let b = (self.b as i16 - blend.b as i16).clamp(0, 255) as u8;
 
         Color::new(r, g, b)
     }
+
+    // `blend` encima de `self`, con alfa no premultiplicado
+    pub fn blend_over(&self, blend: &Color) -> Color {
+        let top_alpha = blend.a as f32 / 255.0;
+        let base_alpha = self.a as f32 / 255.0;
+        let alpha = top_alpha + base_alpha * (1.0 - top_alpha);
+        if alpha <= 0.0 {
+            return Color::new_rgba(0, 0, 0, 0);
+        }
+
+        let channel = |base: u8, top: u8| {
+            ((top as f32 * top_alpha + base as f32 * base_alpha * (1.0 - top_alpha)) / alpha).round() as u8
+        };
+        Color::new_rgba(
+            channel(self.r, blend.r),
+            channel(self.g, blend.g),
+            channel(self.b, blend.b),
+            (alpha * 255.0).round() as u8,
+        )
+    }
+
+    // Tono en grados [0, 360), saturación y valor en [0, 1]
+    #[allow(dead_code)]
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let (r, g, b) = self.to_unit_rgb();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let saturation = if max > 0.0 { (max - min) / max } else { 0.0 };
+        (hue(r, g, b, max, min), saturation, max)
+    }
+
+    #[allow(dead_code)]
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Color {
+        let (saturation, value) = (saturation.clamp(0.0, 1.0), value.clamp(0.0, 1.0));
+        let chroma = value * saturation;
+        Color::from_chroma(hue, chroma, value - chroma)
+    }
+
+    // Tono en grados [0, 360), saturación y luminosidad en [0, 1]
+    #[allow(dead_code)]
+    pub fn to_hsl(self) -> (f32, f32, f32) {
+        let (r, g, b) = self.to_unit_rgb();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let lightness = (max + min) / 2.0;
+        let saturation = if max == min { 0.0 } else { (max - min) / (1.0 - (2.0 * lightness - 1.0).abs()) };
+        (hue(r, g, b, max, min), saturation, lightness)
+    }
+
+    #[allow(dead_code)]
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Color {
+        let (saturation, lightness) = (saturation.clamp(0.0, 1.0), lightness.clamp(0.0, 1.0));
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        Color::from_chroma(hue, chroma, lightness - chroma / 2.0)
+    }
+
+    #[allow(dead_code)]
+    pub fn from_linear(color: LinearColor) -> Color {
+        color.to_srgb()
+    }
+
+    fn to_unit_rgb(self) -> (f32, f32, f32) {
+        (self.r as f32 / 255.0, self.g as f32 / 255.0, self.b as f32 / 255.0)
+    }
+
+    // Construcción común de HSV/HSL a partir del tono, el croma y el mínimo de los canales
+    fn from_chroma(hue: f32, chroma: f32, min: f32) -> Color {
+        let h = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let channel = |c: f32| ((c + min).clamp(0.0, 1.0) * 255.0).round() as u8;
+        Color::new(channel(r), channel(g), channel(b))
+    }
+}
+
+fn hue(r: f32, g: f32, b: f32, max: f32, min: f32) -> f32 {
+    let delta = max - min;
+    if delta <= 0.0 {
+        return 0.0;
+    }
+    let h = if max == r {
+        ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / delta + 2.0
+    } else {
+        (r - g) / delta + 4.0
+    };
+    h * 60.0
+}
+
+// Color lineal en punto flotante para sombreado y el buffer HDR. Los valores pueden
+// superar 1.0 (p. ej. el Sol); el tone mapping los lleva luego al rango presentable.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LinearColor {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+impl LinearColor {
+    pub const fn new(r: f32, g: f32, b: f32) -> Self {
+        LinearColor { r, g, b }
+    }
+
+    pub const fn black() -> Self {
+        LinearColor { r: 0.0, g: 0.0, b: 0.0 }
+    }
+
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
+    pub fn max_component(&self) -> f32 {
+        self.r.max(self.g).max(self.b)
+    }
+
+    pub fn lerp(&self, other: &LinearColor, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        *self + (*other - *self) * t
+    }
+
+    pub fn clamp(self) -> Self {
+        LinearColor::new(self.r.clamp(0.0, 1.0), self.g.clamp(0.0, 1.0), self.b.clamp(0.0, 1.0))
+    }
+
+    // Codificación sRGB con saturación a [0, 1]
+    pub fn to_srgb(self) -> Color {
+        Color::new(linear_to_srgb(self.r), linear_to_srgb(self.g), linear_to_srgb(self.b))
+    }
+}
+
+impl Color {
+    // Decodificación sRGB: los colores de los shaders se escriben en sRGB
+    pub fn to_linear(self) -> LinearColor {
+        LinearColor::new(srgb_to_linear(self.r), srgb_to_linear(self.g), srgb_to_linear(self.b))
+    }
+}
+
+pub fn srgb_to_linear(value: u8) -> f32 {
+    static TABLE: OnceLock<[f32; 256]> = OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        let mut table = [0.0; 256];
+        for (i, entry) in table.iter_mut().enumerate() {
+            let c = i as f32 / 255.0;
+            *entry = if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) };
+        }
+        table
+    });
+    table[value as usize]
+}
+
+pub fn linear_to_srgb(value: f32) -> u8 {
+    const SIZE: usize = 4096;
+    static TABLE: OnceLock<Vec<u8>> = OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        (0..SIZE)
+            .map(|i| {
+                let c = i as f32 / (SIZE - 1) as f32;
+                let encoded = if c <= 0.003_130_8 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 };
+                (encoded * 255.0).round() as u8
+            })
+            .collect()
+    });
+    table[(value.clamp(0.0, 1.0) * (SIZE - 1) as f32) as usize]
+}
+
+impl Add for LinearColor {
+    type Output = LinearColor;
+
+    fn add(self, other: LinearColor) -> LinearColor {
+        LinearColor::new(self.r + other.r, self.g + other.g, self.b + other.b)
+    }
+}
+
+impl AddAssign for LinearColor {
+    fn add_assign(&mut self, other: LinearColor) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for LinearColor {
+    type Output = LinearColor;
+
+    fn sub(self, other: LinearColor) -> LinearColor {
+        LinearColor::new(self.r - other.r, self.g - other.g, self.b - other.b)
+    }
+}
+
+impl Mul<f32> for LinearColor {
+    type Output = LinearColor;
+
+    fn mul(self, scalar: f32) -> LinearColor {
+        LinearColor::new(self.r * scalar, self.g * scalar, self.b * scalar)
+    }
+}
+
+impl Mul<LinearColor> for LinearColor {
+    type Output = LinearColor;
+
+    fn mul(self, other: LinearColor) -> LinearColor {
+        LinearColor::new(self.r * other.r, self.g * other.g, self.b * other.b)
+    }
+}
+
+impl Div<f32> for LinearColor {
+    type Output = LinearColor;
+
+    fn div(self, scalar: f32) -> LinearColor {
+        LinearColor::new(self.r / scalar, self.g / scalar, self.b / scalar)
+    }
 }
 
 use std::ops::Add;
@@ -92,12 +377,34 @@ impl Add for Color {
             r: self.r.saturating_add(other.r),
             g: self.g.saturating_add(other.g),
             b: self.b.saturating_add(other.b),
+            a: self.a,
         }
     }
 }
 
 use std::ops::Mul;
 
+impl Sub for Color {
+    type Output = Color;
+
+    fn sub(self, other: Color) -> Color {
+        Color {
+            r: self.r.saturating_sub(other.r),
+            g: self.g.saturating_sub(other.g),
+            b: self.b.saturating_sub(other.b),
+            a: self.a,
+        }
+    }
+}
+
+impl Mul<Color> for Color {
+    type Output = Color;
+
+    fn mul(self, other: Color) -> Color {
+        self.blend_multiply(&other).with_alpha(self.a)
+    }
+}
+
 impl Mul<f32> for Color {
     type Output = Color;
 
@@ -106,12 +413,78 @@ impl Mul<f32> for Color {
             r: (self.r as f32 * scalar).clamp(0.0, 255.0) as u8,
             g: (self.g as f32 * scalar).clamp(0.0, 255.0) as u8,
             b: (self.b as f32 * scalar).clamp(0.0, 255.0) as u8,
+            a: self.a,
         }
     }
 }
 
 impl fmt::Display for Color {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Color(r: {}, g: {}, b: {})", self.r, self.g, self.b)
+        write!(f, "Color(r: {}, g: {}, b: {}, a: {})", self.r, self.g, self.b, self.a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channels(color: Color) -> (u8, u8, u8, u8) {
+        (color.get_r(), color.get_g(), color.get_b(), color.get_a())
+    }
+
+    // Colores con los seis sectores de tono, grises y extremos
+    const SAMPLES: [Color; 8] = [
+        Color::new(255, 0, 0),
+        Color::new(200, 180, 20),
+        Color::new(30, 220, 90),
+        Color::new(10, 140, 160),
+        Color::new(40, 60, 250),
+        Color::new(170, 20, 200),
+        Color::new(128, 128, 128),
+        Color::new(0, 0, 0),
+    ];
+
+    #[test]
+    fn hsv_round_trip() {
+        for color in SAMPLES {
+            let (hue, saturation, value) = color.to_hsv();
+            assert_eq!(channels(Color::from_hsv(hue, saturation, value)), channels(color), "{}", color);
+        }
+    }
+
+    #[test]
+    fn hsl_round_trip() {
+        for color in SAMPLES {
+            let (hue, saturation, lightness) = color.to_hsl();
+            assert_eq!(channels(Color::from_hsl(hue, saturation, lightness)), channels(color), "{}", color);
+        }
+    }
+
+    #[test]
+    fn hsv_and_hsl_known_values() {
+        assert_eq!(channels(Color::from_hsv(120.0, 1.0, 1.0)), (0, 255, 0, 255));
+        assert_eq!(channels(Color::from_hsl(240.0, 1.0, 0.5)), (0, 0, 255, 255));
+        let (hue, saturation, lightness) = Color::new(255, 0, 255).to_hsl();
+        assert_eq!((hue, saturation, lightness), (300.0, 1.0, 0.5));
+    }
+
+    #[test]
+    fn screen_and_overlay_known_values() {
+        let base = Color::new(64, 128, 255);
+        assert_eq!(channels(base.blend(&Color::new(128, 128, 128), BlendMode::Screen)), (160, 192, 255, 255));
+        // Con el color de mezcla a media opacidad el resultado queda a mitad de camino
+        let translucent = Color::new_rgba(128, 128, 128, 128);
+        assert_eq!(channels(base.blend(&translucent, BlendMode::Screen)), (112, 160, 255, 255));
+
+        let base = Color::new(64, 200, 255);
+        assert_eq!(channels(base.blend(&Color::new(200, 64, 0), BlendMode::Overlay)), (100, 173, 255, 255));
+    }
+
+    #[test]
+    fn alpha_over_composites_with_both_alphas() {
+        let base = Color::new(0, 0, 0);
+        let top = Color::new_rgba(255, 255, 255, 51); // 20 % de opacidad
+        assert_eq!(channels(base.blend(&top, BlendMode::AlphaOver)), (51, 51, 51, 255));
+        assert_eq!(channels(Color::new_rgba(0, 0, 0, 0).blend(&top, BlendMode::AlphaOver)), (255, 255, 255, 51));
     }
 }
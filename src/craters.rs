@@ -0,0 +1,219 @@
+use nalgebra_glm::Vec3;
+use crate::noise::{smoothstep, value_noise, Random};
+
+// Celdas por eje de la rejilla que indexa los cráteres sobre el cubo [-1, 1]³
+const GRID_SIZE: usize = 16;
+
+// Ancho de la pendiente exterior del borde, en radios del cráter
+const RIM_WIDTH: f32 = 0.6;
+
+// Suavizado de las uniones entre cuenco, fondo y borde
+const PROFILE_SMOOTHNESS: f32 = 0.25;
+
+// Los rayos llegan más lejos que el manto de eyección, en múltiplos de `ejecta_reach`
+const RAY_REACH: f32 = 2.5;
+
+// Parámetros de una población de cráteres. Los radios son angulares (radianes sobre la
+// esfera unitaria) y los tamaños siguen una ley de potencias: la cantidad de cráteres de
+// radio mayor que r es proporcional a r^-size_exponent, así hay muchos chicos y pocos grandes.
+#[derive(Debug, Clone, Copy)]
+pub struct CraterParams {
+    pub count: usize,
+    pub min_radius: f32,
+    pub max_radius: f32,
+    pub size_exponent: f32,
+    pub depth: f32,        // profundidad del centro en radios del cráter
+    pub rim_height: f32,   // altura del borde en fracción de la profundidad
+    pub ejecta_reach: f32, // distancia hasta donde llega el material eyectado, en radios del cráter
+    pub ray_fraction: f32, // fracción de cráteres jóvenes con rayos brillantes
+    pub seed: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Crater {
+    center: Vec3, // dirección unitaria
+    radius: f32,
+    floor: f32,   // altura del fondo plano en el perfil normalizado (-1 es un cuenco sin fondo)
+    rays: bool,
+    ray_seed: u32,
+}
+
+// Lo que aportan los cráteres en un punto de la superficie
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CraterSample {
+    pub height: f32, // en radios del cuerpo, negativo dentro de los cuencos
+    pub floor: f32,  // 0 a 1, cuánto del fondo de un cráter hay en el punto
+    pub ejecta: f32, // 0 a 1, brillo del material eyectado y de los rayos
+}
+
+// Cráteres esparcidos al azar (con semilla) sobre la esfera. Cada uno se guarda en todas las
+// celdas de la rejilla que toca su zona de influencia, así evaluar un punto sólo recorre
+// los cráteres de su celda.
+#[derive(Debug)]
+pub struct CraterField {
+    params: CraterParams,
+    craters: Vec<Crater>,
+    cells: Vec<Vec<u32>>,
+    max_height: f32,
+}
+
+impl CraterField {
+    pub fn generate(params: CraterParams) -> Self {
+        let mut random = Random::new(params.seed);
+        let mut craters = Vec::with_capacity(params.count);
+        for _ in 0..params.count {
+            // Muestreo por inversa de la distribución acumulada de la ley de potencias
+            let range = 1.0 - (params.min_radius / params.max_radius).powf(params.size_exponent);
+            let radius = params.min_radius * (1.0 - random.next_f32() * range).powf(-1.0 / params.size_exponent);
+
+            // Los cráteres grandes tienen fondo plano; los chicos son cuencos
+            let size = ((radius - params.min_radius) / (params.max_radius - params.min_radius)).clamp(0.0, 1.0);
+            // Sólo los cráteres jóvenes conservan rayos, y los grandes son todos antiguos
+            let young = random.next_f32() < params.ray_fraction;
+            craters.push(Crater {
+                center: random.unit_vector(),
+                radius,
+                floor: -1.0 + 0.6 * size.sqrt(),
+                rays: young && size < 0.25,
+                ray_seed: random.next_u32(),
+            });
+        }
+
+        let mut cells = vec![Vec::new(); GRID_SIZE * GRID_SIZE * GRID_SIZE];
+        for (index, crater) in craters.iter().enumerate() {
+            let reach = crater.radius * influence(&params, crater);
+            let low = (crater.center.add_scalar(-reach)).map(cell_coordinate);
+            let high = (crater.center.add_scalar(reach)).map(cell_coordinate);
+            for x in low.x..=high.x {
+                for y in low.y..=high.y {
+                    for z in low.z..=high.z {
+                        cells[cell_index(x, y, z)].push(index as u32);
+                    }
+                }
+            }
+        }
+
+        // Un punto sólo suma los cráteres de su celda y cada uno aporta a lo sumo la altura de
+        // su borde, así que la celda con más borde acumulado acota la altura de todo el campo
+        let max_height = cells
+            .iter()
+            .map(|cell| cell.iter().map(|&index| craters[index as usize].radius * params.depth * params.rim_height).sum())
+            .fold(0.0, f32::max);
+
+        CraterField { params, craters, cells, max_height }
+    }
+
+    // Cota de la altura que los bordes superpuestos suman sobre la superficie, en radios del cuerpo
+    pub fn max_height(&self) -> f32 {
+        self.max_height
+    }
+
+    // Sólo la altura de `sample`, sin el color: es lo que se evalúa varias veces por punto
+    // para calcular normales, así que descarta antes los cráteres lejanos
+    pub fn height(&self, direction: &Vec3) -> f32 {
+        let cell = direction.map(cell_coordinate);
+        let mut height = 0.0;
+        for &index in &self.cells[cell_index(cell.x, cell.y, cell.z)] {
+            let crater = &self.craters[index as usize];
+            let reach = crater.radius * (1.0 + RIM_WIDTH);
+            let distance_squared = (direction - crater.center).magnitude_squared();
+            if distance_squared < reach * reach {
+                let distance = distance_squared.sqrt() / crater.radius;
+                height += profile(distance, crater.floor, self.params.rim_height) * self.params.depth * crater.radius;
+            }
+        }
+        height
+    }
+
+    // Perfiles de cuenco, borde y manto de eyección de todos los cráteres que alcanzan
+    // `direction` (unitaria). Las alturas de cráteres superpuestos se suman.
+    pub fn sample(&self, direction: &Vec3) -> CraterSample {
+        let mut sample = CraterSample::default();
+        let cell = direction.map(cell_coordinate);
+
+        for &index in &self.cells[cell_index(cell.x, cell.y, cell.z)] {
+            let crater = &self.craters[index as usize];
+            // La distancia se mide por la cuerda, que para cráteres chicos es casi el arco
+            let offset = direction - crater.center;
+            let reach = crater.radius * influence(&self.params, crater);
+            if offset.magnitude_squared() >= reach * reach {
+                continue;
+            }
+            let distance = offset.magnitude() / crater.radius;
+
+            sample.height += profile(distance, crater.floor, self.params.rim_height) * self.params.depth * crater.radius;
+            sample.floor = sample.floor.max(1.0 - smoothstep(0.45, 0.85, distance));
+
+            let reach = self.params.ejecta_reach;
+            let blanket = (1.0 - (distance - 1.0) / (reach - 1.0)).clamp(0.0, 1.0);
+            let mut ejecta = if distance > 1.0 { blanket * blanket * 0.5 } else { 0.0 };
+            if crater.rays && distance > 0.8 {
+                // Rayos: franjas según el ángulo alrededor del centro que se apagan con la distancia
+                let around = (offset - crater.center * crater.center.dot(&offset)).try_normalize(f32::EPSILON).unwrap_or(offset);
+                let streak = smoothstep(0.55, 0.8, value_noise(&(around * 6.0), crater.ray_seed));
+                let fade = (1.0 - (distance - 1.0) / (reach * RAY_REACH - 1.0)).clamp(0.0, 1.0);
+                ejecta += streak * fade;
+            }
+            sample.ejecta = sample.ejecta.max(ejecta.min(1.0));
+        }
+        sample
+    }
+}
+
+// Hasta qué distancia (en radios del cráter) llega la influencia de un cráter
+fn influence(params: &CraterParams, crater: &Crater) -> f32 {
+    let reach = params.ejecta_reach.max(1.0 + RIM_WIDTH);
+    if crater.rays { reach * RAY_REACH } else { reach }
+}
+
+// Perfil normalizado a distancia `distance` del centro (en radios del cráter): un cuenco
+// parabólico recortado por el fondo plano, con el borde levantado que cae hacia afuera.
+// Vale -1 en el centro de un cuenco sin fondo y 0 lejos del cráter.
+fn profile(distance: f32, floor: f32, rim_height: f32) -> f32 {
+    let cavity = distance * distance - 1.0;
+    let rim_slope = ((distance - 1.0 - RIM_WIDTH).min(0.0) / RIM_WIDTH).powi(2) * rim_height;
+    smooth_min(smooth_max(cavity, floor, PROFILE_SMOOTHNESS), rim_slope, PROFILE_SMOOTHNESS)
+}
+
+fn smooth_min(a: f32, b: f32, k: f32) -> f32 {
+    let h = (0.5 + 0.5 * (b - a) / k).clamp(0.0, 1.0);
+    b + (a - b) * h - k * h * (1.0 - h)
+}
+
+fn smooth_max(a: f32, b: f32, k: f32) -> f32 {
+    -smooth_min(-a, -b, k)
+}
+
+fn cell_coordinate(value: f32) -> usize {
+    (((value + 1.0) * 0.5 * GRID_SIZE as f32) as isize).clamp(0, GRID_SIZE as isize - 1) as usize
+}
+
+fn cell_index(x: usize, y: usize, z: usize) -> usize {
+    (x * GRID_SIZE + y) * GRID_SIZE + z
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_rims_stay_below_max_height() {
+        let field = CraterField::generate(CraterParams {
+            count: 400,
+            min_radius: 0.02,
+            max_radius: 0.3,
+            size_exponent: 1.5,
+            depth: 0.2,
+            rim_height: 0.3,
+            ejecta_reach: 2.0,
+            ray_fraction: 0.3,
+            seed: 7,
+        });
+        let mut random = Random::new(11);
+        for _ in 0..20_000 {
+            let direction = random.unit_vector();
+            assert!(field.height(&direction) <= field.max_height() + 1e-6);
+            assert!(field.sample(&direction).height <= field.max_height() + 1e-6);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+
+// Esfera envolvente en espacio de objeto, calculada una vez al cargar la malla
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f32,
+}
+
+impl BoundingSphere {
+    // Centro de la caja alineada a los ejes y radio hasta el punto más lejano. No es la
+    // esfera mínima, pero para mallas casi esféricas como los planetas queda muy cerca.
+    pub fn from_points(points: &[Vec3]) -> Self {
+        if points.is_empty() {
+            return BoundingSphere { center: Vec3::zeros(), radius: 0.0 };
+        }
+
+        let (min, max) = points.iter().fold(
+            (Vec3::repeat(f32::INFINITY), Vec3::repeat(f32::NEG_INFINITY)),
+            |(min, max), p| (min.inf(p), max.sup(p)),
+        );
+        let center = (min + max) * 0.5;
+        let radius = points.iter().fold(0.0f32, |radius, p| radius.max((p - center).magnitude()));
+
+        BoundingSphere { center, radius }
+    }
+
+    // La esfera transformada por una matriz de modelo con escala uniforme (como las de
+    // `create_model_matrix`)
+    pub fn transform(&self, matrix: &Mat4) -> Self {
+        let center = matrix * Vec4::new(self.center.x, self.center.y, self.center.z, 1.0);
+        let scale = Vec3::new(matrix[(0, 0)], matrix[(1, 0)], matrix[(2, 0)]).magnitude();
+        BoundingSphere { center: Vec3::new(center.x, center.y, center.z), radius: self.radius * scale }
+    }
+}
+
+// Planos del volumen de vista (izquierda, derecha, abajo, arriba, near, far) con la
+// normal apuntando hacia adentro, extraídos de la matriz de proyección combinada
+// (método de Gribb y Hartmann). Si la matriz incluye la de modelo, los planos quedan
+// en espacio de objeto y la esfera se puede probar sin transformarla.
+pub struct Frustum {
+    planes: [Vec4; 6],
+}
+
+impl Frustum {
+    pub fn from_matrix(matrix: &Mat4) -> Self {
+        let row = |i: usize| Vec4::new(matrix[(i, 0)], matrix[(i, 1)], matrix[(i, 2)], matrix[(i, 3)]);
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+
+        let planes = [w + x, w - x, w + y, w - y, w + z, w - z].map(|plane| {
+            let length = Vec3::new(plane.x, plane.y, plane.z).magnitude();
+            if length > 0.0 { plane / length } else { plane }
+        });
+
+        Frustum { planes }
+    }
+
+    // Conservador: puede aceptar esferas que rozan una esquina fuera del volumen, nunca
+    // descarta una que lo toca
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes.iter().all(|plane| {
+            let distance = plane.x * sphere.center.x + plane.y * sphere.center.y + plane.z * sphere.center.z + plane.w;
+            distance >= -sphere.radius
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CullingStats {
+    pub drawn: usize,
+    pub culled: usize,
+}
+
+impl CullingStats {
+    pub fn record(&mut self, drawn: bool) {
+        if drawn {
+            self.drawn += 1;
+        } else {
+            self.culled += 1;
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Día juliano de la época J2000.0 (1 de enero de 2000, 12:00 TT)
+pub const J2000: f64 = 2451545.0;
+const DAYS_PER_CENTURY: f64 = 36525.0;
+const UNIX_EPOCH_JD: f64 = 2440587.5;
+
+// Elementos keplerianos medios de un planeta respecto de la eclíptica y el equinoccio
+// J2000. Distancias en UA y ángulos en grados, como en la tabla de JPL.
+#[derive(Debug, Clone, Copy)]
+pub struct OrbitalElements {
+    pub semi_major_axis: f64,
+    pub eccentricity: f64,
+    pub inclination: f64,
+    pub mean_longitude: f64,
+    pub longitude_of_perihelion: f64,
+    pub longitude_of_node: f64,
+}
+
+impl OrbitalElements {
+    pub fn mean_anomaly(&self) -> f64 {
+        (self.mean_longitude - self.longitude_of_perihelion).rem_euclid(360.0)
+    }
+
+    pub fn argument_of_perihelion(&self) -> f64 {
+        (self.longitude_of_perihelion - self.longitude_of_node).rem_euclid(360.0)
+    }
+}
+
+// Elementos en J2000 y su variación por siglo juliano
+pub struct PlanetEphemeris {
+    pub elements: OrbitalElements,
+    pub rates: OrbitalElements,
+}
+
+impl PlanetEphemeris {
+    // Elementos medios para un día juliano, con las variaciones seculares lineales
+    pub fn elements_at(&self, julian_date: f64) -> OrbitalElements {
+        let centuries = (julian_date - J2000) / DAYS_PER_CENTURY;
+        let (e, r) = (&self.elements, &self.rates);
+
+        OrbitalElements {
+            semi_major_axis: e.semi_major_axis + r.semi_major_axis * centuries,
+            eccentricity: e.eccentricity + r.eccentricity * centuries,
+            inclination: e.inclination + r.inclination * centuries,
+            mean_longitude: e.mean_longitude + r.mean_longitude * centuries,
+            longitude_of_perihelion: e.longitude_of_perihelion + r.longitude_of_perihelion * centuries,
+            longitude_of_node: e.longitude_of_node + r.longitude_of_node * centuries,
+        }
+    }
+
+    // Período sideral en días según la variación de la longitud media de la tabla. La
+    // tercera ley de Kepler da un período algo distinto y, en una corrida larga, el planeta
+    // se adelanta o se atrasa respecto de las efemérides.
+    pub fn period_days(&self) -> f64 {
+        360.0 * DAYS_PER_CENTURY / self.rates.mean_longitude
+    }
+}
+
+// Tabla 1 de "Keplerian Elements for Approximate Positions of the Major Planets"
+// (E. M. Standish, JPL), válida entre 1800 y 2050 con errores de pocos minutos de arco.
+// Cada fila trae a, e, I, L, ϖ y Ω, en ese orden.
+const fn planet(elements: [f64; 6], rates: [f64; 6]) -> PlanetEphemeris {
+    const fn unpack(values: [f64; 6]) -> OrbitalElements {
+        OrbitalElements {
+            semi_major_axis: values[0],
+            eccentricity: values[1],
+            inclination: values[2],
+            mean_longitude: values[3],
+            longitude_of_perihelion: values[4],
+            longitude_of_node: values[5],
+        }
+    }
+    PlanetEphemeris { elements: unpack(elements), rates: unpack(rates) }
+}
+
+pub const MERCURY: PlanetEphemeris = planet(
+    [0.38709927, 0.20563593, 7.00497902, 252.25032350, 77.45779628, 48.33076593],
+    [0.00000037, 0.00001906, -0.00594749, 149472.67411175, 0.16047689, -0.12534081],
+);
+pub const VENUS: PlanetEphemeris = planet(
+    [0.72333566, 0.00677672, 3.39467605, 181.97909950, 131.60246718, 76.67984255],
+    [0.00000390, -0.00004107, -0.00078890, 58517.81538729, 0.00268329, -0.27769418],
+);
+// Baricentro Tierra-Luna
+pub const EARTH: PlanetEphemeris = planet(
+    [1.00000261, 0.01671123, -0.00001531, 100.46457166, 102.93768193, 0.0],
+    [0.00000562, -0.00004392, -0.01294668, 35999.37244981, 0.32327364, 0.0],
+);
+pub const MARS: PlanetEphemeris = planet(
+    [1.52371034, 0.09339410, 1.84969142, -4.55343205, -23.94362959, 49.55953891],
+    [0.00001847, 0.00007882, -0.00813131, 19140.30268499, 0.44441088, -0.29257343],
+);
+pub const JUPITER: PlanetEphemeris = planet(
+    [5.20288700, 0.04838624, 1.30439695, 34.39644051, 14.72847983, 100.47390909],
+    [-0.00011607, -0.00013253, -0.00183714, 3034.74612775, 0.21252668, 0.20469106],
+);
+pub const SATURN: PlanetEphemeris = planet(
+    [9.53667594, 0.05386179, 2.48599187, 49.95424423, 92.59887831, 113.66242448],
+    [-0.00125060, -0.00050991, 0.00193609, 1222.49362201, -0.41897216, -0.28867794],
+);
+pub const URANUS: PlanetEphemeris = planet(
+    [19.18916464, 0.04725744, 0.77263783, 313.23810451, 170.95427630, 74.01692503],
+    [-0.00196176, -0.00004397, -0.00242939, 428.48202785, 0.40805281, 0.04240589],
+);
+pub const NEPTUNE: PlanetEphemeris = planet(
+    [30.06992276, 0.00859048, 1.77004347, -55.12002969, 44.96476227, 131.78422574],
+    [0.00026291, 0.00005105, 0.00035372, 218.45945325, -0.32241464, -0.00508664],
+);
+
+// Día juliano a las 0 h de una fecha del calendario gregoriano (Meeus, cap. 7)
+pub fn julian_date(year: i32, month: u32, day: u32) -> f64 {
+    let (year, month) = if month <= 2 { (year - 1, month + 12) } else { (year, month) };
+    let century = (year as f64 / 100.0).floor();
+    let gregorian = 2.0 - century + (century / 4.0).floor();
+
+    (365.25 * (year as f64 + 4716.0)).floor() + (30.6001 * (month as f64 + 1.0)).floor() + day as f64 + gregorian - 1524.5
+}
+
+// Inversa de `julian_date`: (año, mes, día) del calendario gregoriano
+pub fn calendar_date(julian_date: f64) -> (i32, u32, u32) {
+    let z = (julian_date + 0.5).floor();
+    let alpha = ((z - 1867216.25) / 36524.25).floor();
+    let a = z + 1.0 + alpha - (alpha / 4.0).floor();
+    let b = a + 1524.0;
+    let c = ((b - 122.1) / 365.25).floor();
+    let d = (365.25 * c).floor();
+    let e = ((b - d) / 30.6001).floor();
+
+    let day = (b - d - (30.6001 * e).floor()) as u32;
+    let month = (if e < 14.0 { e - 1.0 } else { e - 13.0 }) as u32;
+    let year = (if month > 2 { c - 4716.0 } else { c - 4715.0 }) as i32;
+    (year, month, day)
+}
+
+pub fn format_date(julian_date: f64) -> String {
+    let (year, month, day) = calendar_date(julian_date);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+// Fecha en formato AAAA-MM-DD
+pub fn parse_date(text: &str) -> Result<f64, String> {
+    let invalid = || format!("fecha inválida '{}', se esperaba AAAA-MM-DD", text);
+
+    let mut parts = text.trim().splitn(3, '-');
+    let (Some(year), Some(month), Some(day)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(invalid());
+    };
+    let year: i32 = year.parse().map_err(|_| invalid())?;
+    let month: u32 = month.parse().map_err(|_| invalid())?;
+    let day: u32 = day.parse().map_err(|_| invalid())?;
+
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return Err(invalid());
+    }
+
+    Ok(julian_date(year, month, day))
+}
+
+// Día juliano del momento actual según el reloj del sistema
+pub fn today() -> f64 {
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs_f64()).unwrap_or(0.0);
+    UNIX_EPOCH_JD + seconds / 86400.0
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn julian_date_known_values() {
+        assert_eq!(julian_date(2000, 1, 1), 2451544.5);
+        assert_eq!(julian_date(2000, 1, 1) + 0.5, J2000);
+        // Ejemplos 7.a y 7.b de Meeus
+        assert_eq!(julian_date(1957, 10, 4), 2436115.5);
+        assert_eq!(julian_date(1987, 1, 27), 2446822.5);
+    }
+
+    #[test]
+    fn calendar_date_round_trip() {
+        let mut previous = julian_date(1799, 12, 31);
+        for year in 1800..=2100 {
+            for month in 1..=12 {
+                for day in 1..=days_in_month(year, month) {
+                    let date = julian_date(year, month, day);
+                    assert_eq!(date, previous + 1.0, "{}-{}-{}", year, month, day);
+                    assert_eq!(calendar_date(date), (year, month, day));
+                    // Cualquier hora del día cae en la misma fecha
+                    assert_eq!(calendar_date(date + 0.75), (year, month, day));
+                    previous = date;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parse_and_format_dates() {
+        assert_eq!(parse_date("2000-01-01"), Ok(2451544.5));
+        assert_eq!(parse_date(" 2024-02-29 ").map(format_date), Ok("2024-02-29".to_string()));
+        assert_eq!(format_date(J2000), "2000-01-01");
+
+        for text in ["2023-02-29", "1900-02-29", "2000-13-01", "2000-04-31", "2000-01-00", "2000-01", "hoy"] {
+            assert!(parse_date(text).is_err(), "{}", text);
+        }
+    }
+}
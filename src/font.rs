@@ -0,0 +1,132 @@
+use crate::framebuffer::Framebuffer;
+
+// Fuente de mapa de bits de 5×7 píxeles para los textos dibujados sobre la imagen final.
+// Sólo tiene mayúsculas: las minúsculas se dibujan como mayúsculas y las vocales con tilde
+// y la Ñ como la letra base con un acento de dos filas encima.
+pub const GLYPH_WIDTH: usize = 5;
+pub const GLYPH_HEIGHT: usize = 7;
+
+// Avance horizontal por carácter y alto de una línea (con lugar para los acentos), en
+// píxeles de la fuente
+pub const ADVANCE: usize = GLYPH_WIDTH + 1;
+pub const LINE_HEIGHT: usize = GLYPH_HEIGHT + ACCENT_HEIGHT + 2;
+
+pub const ACCENT_HEIGHT: usize = 2;
+
+// Cada fila es un byte con el píxel de más a la izquierda en el bit 4
+const GLYPHS: [(char, [u8; GLYPH_HEIGHT]); 48] = [
+    ('A', [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('B', [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110]),
+    ('C', [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110]),
+    ('D', [0b11110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b11110]),
+    ('E', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111]),
+    ('F', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('G', [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111]),
+    ('H', [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('I', [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('J', [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100]),
+    ('K', [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001]),
+    ('L', [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111]),
+    ('M', [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001]),
+    ('N', [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001]),
+    ('O', [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('P', [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('Q', [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101]),
+    ('R', [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001]),
+    ('S', [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110]),
+    ('T', [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100]),
+    ('U', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('V', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100]),
+    ('W', [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010]),
+    ('X', [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001]),
+    ('Y', [0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100, 0b00100]),
+    ('Z', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111]),
+    ('0', [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110]),
+    ('1', [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('2', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111]),
+    ('3', [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110]),
+    ('4', [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010]),
+    ('5', [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110]),
+    ('6', [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110]),
+    ('7', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000]),
+    ('8', [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110]),
+    ('9', [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100]),
+    ('.', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100]),
+    (',', [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000]),
+    (':', [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000]),
+    ('-', [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000]),
+    ('+', [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000]),
+    ('=', [0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000]),
+    ('/', [0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000]),
+    ('(', [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010]),
+    (')', [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000]),
+    ('%', [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011]),
+    ('°', [0b01100, 0b10010, 0b10010, 0b01100, 0b00000, 0b00000, 0b00000]),
+    ('?', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100]),
+];
+
+const ACUTE: [u8; ACCENT_HEIGHT] = [0b00010, 0b00100];
+const TILDE: [u8; ACCENT_HEIGHT] = [0b01101, 0b10010];
+const DIAERESIS: [u8; ACCENT_HEIGHT] = [0b01010, 0b00000];
+
+// Letra base y acento de un carácter, ya en mayúsculas
+fn decompose(character: char) -> (char, Option<&'static [u8; ACCENT_HEIGHT]>) {
+    match character.to_uppercase().next().unwrap_or(character) {
+        'Á' => ('A', Some(&ACUTE)),
+        'É' => ('E', Some(&ACUTE)),
+        'Í' => ('I', Some(&ACUTE)),
+        'Ó' => ('O', Some(&ACUTE)),
+        'Ú' => ('U', Some(&ACUTE)),
+        'Ü' => ('U', Some(&DIAERESIS)),
+        'Ñ' => ('N', Some(&TILDE)),
+        other => (other, None),
+    }
+}
+
+fn glyph(character: char) -> Option<&'static [u8; GLYPH_HEIGHT]> {
+    GLYPHS.iter().find(|(glyph, _)| *glyph == character).map(|(_, rows)| rows)
+}
+
+// Ancho en píxeles de pantalla de `text` dibujado con `scale`
+pub fn text_width(text: &str, scale: usize) -> usize {
+    (text.chars().count() * ADVANCE).saturating_sub(1) * scale
+}
+
+// Dibuja `text` en el buffer de salida (ya tone-mapeado) con la esquina superior izquierda
+// del primer carácter en (x, y). Los caracteres sin glifo se dibujan como '?', salvo el
+// espacio; lo que cae fuera de la pantalla se recorta.
+pub fn draw_text(framebuffer: &mut Framebuffer, x: i32, y: i32, text: &str, color: u32, scale: usize) {
+    for (index, character) in text.chars().enumerate() {
+        if character == ' ' {
+            continue;
+        }
+
+        let (base, accent) = decompose(character);
+        let left = x + (index * ADVANCE * scale) as i32;
+        let rows = glyph(base).or_else(|| glyph('?')).expect("la fuente incluye '?'");
+
+        draw_rows(framebuffer, left, y, rows, color, scale);
+        if let Some(accent) = accent {
+            draw_rows(framebuffer, left, y - (ACCENT_HEIGHT * scale) as i32, accent, color, scale);
+        }
+    }
+}
+
+fn draw_rows(framebuffer: &mut Framebuffer, x: i32, y: i32, rows: &[u8], color: u32, scale: usize) {
+    for (row, bits) in rows.iter().enumerate() {
+        for column in 0..GLYPH_WIDTH {
+            if bits & (1 << (GLYPH_WIDTH - 1 - column)) == 0 {
+                continue;
+            }
+            for dy in 0..scale {
+                for dx in 0..scale {
+                    let px = x + (column * scale + dx) as i32;
+                    let py = y + (row * scale + dy) as i32;
+                    if px >= 0 && py >= 0 && (px as usize) < framebuffer.output_width && (py as usize) < framebuffer.output_height {
+                        framebuffer.buffer[py as usize * framebuffer.output_width + px as usize] = color;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
 
 use nalgebra_glm::{Vec2, Vec3};
 use crate::color::Color;
+use crate::framebuffer::MAX_SAMPLES;
 
 pub struct Fragment {
     pub position: Vec2,
@@ -9,6 +10,8 @@ pub struct Fragment {
     pub normal: Vec3,
     pub intensity: f32,
     pub vertex_position: Vec3,
+    pub coverage: u8,
+    pub sample_depths: [f32; MAX_SAMPLES],
 }
 
 impl Fragment {
@@ -19,7 +22,9 @@ impl Fragment {
             depth,
             normal,
             intensity,
-            vertex_position
+            vertex_position,
+            coverage: u8::MAX,
+            sample_depths: [depth; MAX_SAMPLES],
         }
     }
 }
\ No newline at end of file
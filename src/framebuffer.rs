@@ -1,50 +1,295 @@
+use crate::color::{Color, LinearColor};
+use crate::postprocess::ToneMapping;
+
+pub const MAX_SAMPLES: usize = 4;
+
+// Posiciones de muestreo dentro del píxel. Con 4 muestras se usa una rejilla rotada,
+// que resuelve mejor los bordes casi horizontales o verticales que una rejilla alineada.
+const PATTERN_1X: [(f32, f32); 1] = [(0.5, 0.5)];
+const PATTERN_2X: [(f32, f32); 2] = [(0.25, 0.25), (0.75, 0.75)];
+const PATTERN_4X: [(f32, f32); 4] = [(0.375, 0.125), (0.875, 0.375), (0.125, 0.625), (0.625, 0.875)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AntiAliasing {
+    None,
+    Fxaa,        // filtro en espacio de pantalla sobre la imagen final
+    Ssaa(usize), // se renderiza a N× la resolución en cada eje
+    Msaa(usize), // N muestras de cobertura y profundidad por píxel
+}
+
+impl AntiAliasing {
+    pub fn name(self) -> String {
+        match self {
+            AntiAliasing::None => "Sin AA".to_string(),
+            AntiAliasing::Fxaa => "FXAA".to_string(),
+            AntiAliasing::Ssaa(factor) => format!("SSAA {}x{}", factor, factor),
+            AntiAliasing::Msaa(samples) => format!("MSAA {}x", samples),
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            AntiAliasing::None => AntiAliasing::Fxaa,
+            AntiAliasing::Fxaa => AntiAliasing::Ssaa(2),
+            AntiAliasing::Ssaa(2) => AntiAliasing::Ssaa(3),
+            AntiAliasing::Ssaa(_) => AntiAliasing::Msaa(4),
+            AntiAliasing::Msaa(_) => AntiAliasing::None,
+        }
+    }
+}
 
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
+    pub output_width: usize,
+    pub output_height: usize,
+    pub ssaa: usize,
+    pub samples: usize,
     pub buffer: Vec<u32>,
+    pub color_buffer: Vec<LinearColor>,
     pub zbuffer: Vec<f32>,
-    background_color: u32,
-    current_color: u32,
+    sample_buffer: Vec<LinearColor>,
+    background_color: LinearColor,
+    current_color: LinearColor,
 }
 
 impl Framebuffer {
     pub fn new(width: usize, height: usize) -> Self {
-        Framebuffer {
+        Framebuffer::with_anti_aliasing(width, height, AntiAliasing::None)
+    }
+
+    // `width` y `height` son la resolución de salida; con SSAA el render interno es mayor
+    pub fn with_anti_aliasing(width: usize, height: usize, anti_aliasing: AntiAliasing) -> Self {
+        let mut framebuffer = Framebuffer {
             width,
             height,
+            output_width: width,
+            output_height: height,
+            ssaa: 1,
+            samples: 1,
             buffer: vec![0; width * height],
-            zbuffer: vec![f32::INFINITY; width * height],
-            background_color: 0x000000,
-            current_color: 0xFFFFFF
+            color_buffer: Vec::new(),
+            zbuffer: Vec::new(),
+            sample_buffer: Vec::new(),
+            background_color: LinearColor::black(),
+            current_color: LinearColor::new(1.0, 1.0, 1.0)
+        };
+        framebuffer.set_anti_aliasing(anti_aliasing);
+        framebuffer
+    }
+
+    pub fn set_anti_aliasing(&mut self, anti_aliasing: AntiAliasing) {
+        let (ssaa, samples) = match anti_aliasing {
+            AntiAliasing::None | AntiAliasing::Fxaa => (1, 1),
+            AntiAliasing::Ssaa(factor) => (factor.max(1), 1),
+            AntiAliasing::Msaa(samples) => (1, samples.clamp(1, MAX_SAMPLES)),
+        };
+
+        self.ssaa = ssaa;
+        self.samples = samples;
+        self.width = self.output_width * ssaa;
+        self.height = self.output_height * ssaa;
+
+        let pixels = self.width * self.height;
+        self.color_buffer = vec![self.background_color; pixels];
+        self.zbuffer = vec![f32::INFINITY; pixels * samples];
+        self.sample_buffer = if samples > 1 { vec![self.background_color; pixels * samples] } else { Vec::new() };
+    }
+
+    pub fn sample_pattern(&self) -> &'static [(f32, f32)] {
+        match self.samples {
+            1 => &PATTERN_1X,
+            2 => &PATTERN_2X,
+            _ => &PATTERN_4X,
         }
     }
 
     pub fn clear(&mut self) {
-        for pixel in self.buffer.iter_mut() {
+        for pixel in self.color_buffer.iter_mut() {
             *pixel = self.background_color;
         }
+        for sample in self.sample_buffer.iter_mut() {
+            *sample = self.background_color;
+        }
         for depth in self.zbuffer.iter_mut() {
             *depth = f32::INFINITY;
         }
     }
 
+    #[allow(dead_code)]
     pub fn point(&mut self, x: usize, y: usize, depth: f32) {
-        if x < self.width && y < self.height {
-            let index = y * self.width + x;
+        self.point_samples(x, y, u8::MAX, &[depth; MAX_SAMPLES]);
+    }
+
+    // Escribe `current_color` en las muestras indicadas por `coverage` que pasan la prueba
+    // de profundidad. El color se calcula una vez por píxel; sólo la profundidad es por muestra.
+    pub fn point_samples(&mut self, x: usize, y: usize, coverage: u8, depths: &[f32; MAX_SAMPLES]) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let index = y * self.width + x;
+
+        if self.samples == 1 {
+            if coverage & 1 != 0 && self.zbuffer[index] > depths[0] {
+                self.color_buffer[index] = self.current_color;
+                self.zbuffer[index] = depths[0];
+            }
+            return;
+        }
+
+        for (sample, depth) in depths.iter().enumerate().take(self.samples) {
+            let sample_index = index * self.samples + sample;
+            if coverage & (1 << sample) != 0 && self.zbuffer[sample_index] > *depth {
+                self.sample_buffer[sample_index] = self.current_color;
+                self.zbuffer[sample_index] = *depth;
+            }
+        }
+    }
 
+    // Como `point_samples` pero sólo actualiza el zbuffer (pasada de geometría diferida)
+    pub fn depth_samples(&mut self, x: usize, y: usize, coverage: u8, depths: &[f32; MAX_SAMPLES]) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let index = y * self.width + x;
+
+        for (sample, depth) in depths.iter().enumerate().take(self.samples) {
+            let sample_index = index * self.samples + sample;
+            if coverage & (1 << sample) != 0 && self.zbuffer[sample_index] > *depth {
+                self.zbuffer[sample_index] = *depth;
+            }
+        }
+    }
+
+    // Escribe `current_color` en las muestras de `coverage` sin prueba de profundidad
+    // (pasada de sombreado diferida, que ya resolvió la visibilidad)
+    pub fn shade_samples(&mut self, x: usize, y: usize, coverage: u8) {
+        let index = y * self.width + x;
+
+        if self.samples == 1 {
+            if coverage & 1 != 0 {
+                self.color_buffer[index] = self.current_color;
+            }
+            return;
+        }
+
+        for sample in 0..self.samples {
+            if coverage & (1 << sample) != 0 {
+                self.sample_buffer[index * self.samples + sample] = self.current_color;
+            }
+        }
+    }
+
+    // Suma `current_color` a las muestras cubiertas que pasan la prueba de profundidad, sin
+    // escribir en el zbuffer. Para capas translúcidas emisivas como la atmósfera.
+    pub fn add_samples(&mut self, x: usize, y: usize, coverage: u8, depths: &[f32; MAX_SAMPLES]) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let index = y * self.width + x;
+
+        if self.samples == 1 {
+            if coverage & 1 != 0 && self.zbuffer[index] > depths[0] {
+                self.color_buffer[index] += self.current_color;
+            }
+            return;
+        }
+
+        for (sample, depth) in depths.iter().enumerate().take(self.samples) {
+            let sample_index = index * self.samples + sample;
+            if coverage & (1 << sample) != 0 && self.zbuffer[sample_index] > *depth {
+                self.sample_buffer[sample_index] += self.current_color;
+            }
+        }
+    }
+
+    // Prueba de profundidad sin escritura en el zbuffer, mezclando `current_color`
+    // sobre el color existente según `alpha`. Se usa para líneas y trazos.
+    pub fn blend_point(&mut self, x: usize, y: usize, depth: f32, alpha: f32) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let index = y * self.width + x;
+
+        if self.samples == 1 {
             if self.zbuffer[index] > depth {
-                self.buffer[index] = self.current_color;
-                self.zbuffer[index] = depth;
+                self.color_buffer[index] = self.color_buffer[index].lerp(&self.current_color, alpha);
+            }
+            return;
+        }
+
+        for sample_index in index * self.samples..(index + 1) * self.samples {
+            if self.zbuffer[sample_index] > depth {
+                self.sample_buffer[sample_index] = self.sample_buffer[sample_index].lerp(&self.current_color, alpha);
+            }
+        }
+    }
+
+    // Escritura directa de fondo (cielo) en todas las muestras del píxel, sin profundidad
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: LinearColor) {
+        let index = y * self.width + x;
+        self.color_buffer[index] = color;
+        if self.samples > 1 {
+            for sample in &mut self.sample_buffer[index * self.samples..(index + 1) * self.samples] {
+                *sample = color;
+            }
+        }
+    }
+
+    pub fn add_pixel(&mut self, x: usize, y: usize, color: LinearColor) {
+        let index = y * self.width + x;
+        self.color_buffer[index] += color;
+        if self.samples > 1 {
+            for sample in &mut self.sample_buffer[index * self.samples..(index + 1) * self.samples] {
+                *sample += color;
+            }
+        }
+    }
+
+    // Profundidad más cercana entre las muestras del píxel
+    pub fn pixel_depth(&self, index: usize) -> f32 {
+        self.zbuffer[index * self.samples..(index + 1) * self.samples]
+            .iter()
+            .fold(f32::INFINITY, |min, &depth| min.min(depth))
+    }
+
+    // Promedia las muestras MSAA en `color_buffer`. Debe llamarse después de dibujar la
+    // geometría y antes del post-procesado.
+    pub fn resolve_samples(&mut self) {
+        if self.samples == 1 {
+            return;
+        }
+        for (pixel, samples) in self.color_buffer.iter_mut().zip(self.sample_buffer.chunks(self.samples)) {
+            let sum = samples.iter().fold(LinearColor::black(), |sum, &sample| sum + sample);
+            *pixel = sum / self.samples as f32;
+        }
+    }
+
+    // Convierte el buffer HDR lineal en el buffer presentable: exposición, tone mapping,
+    // reducción de SSAA (promedio por bloques ya en rango [0, 1]) y codificación sRGB
+    pub fn resolve(&mut self, tone_mapping: ToneMapping, exposure: f32) {
+        let ssaa = self.ssaa;
+        let weight = 1.0 / (ssaa * ssaa) as f32;
+
+        for y in 0..self.output_height {
+            for x in 0..self.output_width {
+                let mut sum = LinearColor::black();
+                for sy in 0..ssaa {
+                    let row = (y * ssaa + sy) * self.width + x * ssaa;
+                    for color in &self.color_buffer[row..row + ssaa] {
+                        sum += tone_mapping.apply(*color * exposure).clamp();
+                    }
+                }
+                self.buffer[y * self.output_width + x] = (sum * weight).to_srgb().to_hex();
             }
         }
     }
 
     pub fn set_background_color(&mut self, color: u32) {
-        self.background_color = color;
+        self.background_color = Color::from_hex(color).to_linear();
     }
 
-    pub fn set_current_color(&mut self, color: u32) {
+    pub fn set_current_color(&mut self, color: LinearColor) {
         self.current_color = color;
     }
 }
@@ -0,0 +1,128 @@
+use nalgebra_glm::Vec3;
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::framebuffer::Framebuffer;
+use crate::{FragmentShader, Uniforms};
+
+// Material 0 marca los píxeles sin geometría
+pub const NO_MATERIAL: u8 = 0;
+
+// Shader y uniforms con los que se sombrea un cuerpo en la pasada diferida
+pub struct Material {
+    pub shader: FragmentShader,
+    pub uniforms: Uniforms,
+}
+
+// Atributos del fragmento visible en cada muestra, a la resolución de render del
+// framebuffer (con MSAA hay `samples` entradas por píxel). La pasada de geometría sólo
+// rasteriza y guarda aquí; la de sombreado ejecuta el fragment shader una vez por píxel
+// y material visible, sin coste por el overdraw.
+pub struct GBuffer {
+    pub width: usize,
+    pub height: usize,
+    pub samples: usize,
+    pub depth: Vec<f32>,
+    pub normal: Vec<Vec3>,
+    pub position: Vec<Vec3>, // posición en espacio de objeto
+    pub intensity: Vec<f32>,
+    pub color: Vec<Color>,
+    pub material: Vec<u8>,
+    materials: Vec<Material>,
+}
+
+impl GBuffer {
+    pub fn new(width: usize, height: usize, samples: usize) -> Self {
+        let entries = width * height * samples;
+        GBuffer {
+            width,
+            height,
+            samples,
+            depth: vec![f32::INFINITY; entries],
+            normal: vec![Vec3::zeros(); entries],
+            position: vec![Vec3::zeros(); entries],
+            intensity: vec![0.0; entries],
+            color: vec![Color::black(); entries],
+            material: vec![NO_MATERIAL; entries],
+            materials: Vec::new(),
+        }
+    }
+
+    // Limpia el buffer y lo adapta si cambió la configuración de antialiasing del framebuffer
+    pub fn clear(&mut self, framebuffer: &Framebuffer) {
+        if framebuffer.width != self.width || framebuffer.height != self.height || framebuffer.samples != self.samples {
+            *self = GBuffer::new(framebuffer.width, framebuffer.height, framebuffer.samples);
+            return;
+        }
+        self.depth.fill(f32::INFINITY);
+        self.material.fill(NO_MATERIAL);
+        self.materials.clear();
+    }
+
+    // Registra el material de un cuerpo para este frame y devuelve su ID
+    pub fn add_material(&mut self, shader: FragmentShader, uniforms: Uniforms) -> u8 {
+        self.materials.push(Material { shader, uniforms });
+        assert!(self.materials.len() <= u8::MAX as usize, "demasiados materiales en el G-buffer");
+        self.materials.len() as u8
+    }
+
+    // Pasada de geometría: prueba de profundidad por muestra cubierta
+    pub fn write(&mut self, x: usize, y: usize, fragment: &Fragment, material: u8) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let index = (y * self.width + x) * self.samples;
+
+        for sample in 0..self.samples {
+            let entry = index + sample;
+            if fragment.coverage & (1 << sample) == 0 || fragment.sample_depths[sample] >= self.depth[entry] {
+                continue;
+            }
+
+            self.depth[entry] = fragment.sample_depths[sample];
+            self.normal[entry] = fragment.normal;
+            self.position[entry] = fragment.vertex_position;
+            self.intensity[entry] = fragment.intensity;
+            self.color[entry] = fragment.color;
+            self.material[entry] = material;
+        }
+    }
+
+    // Pasada de sombreado. Las muestras de un mismo material dentro del píxel comparten una
+    // sola invocación del shader, así que sólo los bordes entre cuerpos se sombrean más de
+    // una vez (como en MSAA directo).
+    pub fn shade(&self, framebuffer: &mut Framebuffer) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let index = (y * self.width + x) * self.samples;
+                let mut shaded = 0u8;
+
+                for sample in 0..self.samples {
+                    let entry = index + sample;
+                    let material = self.material[entry];
+                    if material == NO_MATERIAL || shaded & (1 << sample) != 0 {
+                        continue;
+                    }
+
+                    let coverage = (sample..self.samples)
+                        .filter(|&other| self.material[index + other] == material)
+                        .fold(0u8, |mask, other| mask | (1 << other));
+                    shaded |= coverage;
+
+                    let fragment = Fragment::new(
+                        x as f32,
+                        y as f32,
+                        self.color[entry],
+                        self.depth[entry],
+                        self.normal[entry],
+                        self.intensity[entry],
+                        self.position[entry],
+                    );
+
+                    let Material { shader, uniforms } = &self.materials[material as usize - 1];
+                    framebuffer.set_current_color(shader(&fragment, uniforms));
+                    framebuffer.shade_samples(x, y, coverage);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use nalgebra_glm::{Mat3, Mat4, Vec2, Vec3, Vec4};
+use std::path::Path;
+use gltf::buffer::Data;
+use gltf::mesh::Mode;
+use crate::color::LinearColor;
+use crate::culling::BoundingSphere;
+use crate::vertex::Vertex;
+use crate::mesh::{Mesh, DEFAULT_SMOOTHING_ANGLE};
+
+// Modelo glTF 2.0 (.gltf con buffers externos o embebidos como data URI, o .glb). Cada
+// primitiva de la escena se guarda ya transformada por la jerarquía de nodos, así
+// `mesh` entrega los mismos `Vertex` que `Obj` y se dibuja igual. El factor de
+// color base del material queda en `Vertex::color`; las texturas se ignoran.
+pub struct GltfModel {
+    mesh: Mesh,
+    bounding_sphere: BoundingSphere,
+}
+
+impl GltfModel {
+    pub fn load(filename: &str) -> Result<Self, String> {
+        let bytes = std::fs::read(filename).map_err(|error| format!("{}: {}", filename, error))?;
+        let base_dir = Path::new(filename).parent().unwrap_or(Path::new(""));
+        GltfModel::from_slice(&bytes, base_dir).map_err(|error| format!("{}: {}", filename, error))
+    }
+
+    // `base_dir` es el directorio contra el que se resuelven las URI de los buffers externos
+    pub fn from_slice(bytes: &[u8], base_dir: &Path) -> Result<Self, String> {
+        let gltf::Gltf { document: gltf, blob } = gltf::Gltf::from_slice(bytes).map_err(|error| error.to_string())?;
+        // Bloque binario del .glb, data URI en base64 o archivos externos con la URI decodificada
+        let buffers = gltf::import_buffers(&gltf, Some(base_dir), blob).map_err(|error| error.to_string())?;
+
+        let mut mesh = Mesh::default();
+        match gltf.default_scene().or_else(|| gltf.scenes().next()) {
+            Some(scene) => {
+                for node in scene.nodes() {
+                    load_node(&node, &Mat4::identity(), &buffers, &mut mesh)?;
+                }
+            }
+            // Archivo sin escenas: las mallas se toman tal cual
+            None => {
+                for gltf_mesh in gltf.meshes() {
+                    load_mesh(&gltf_mesh, &Mat4::identity(), &buffers, &mut mesh)?;
+                }
+            }
+        }
+
+        if mesh.indices.is_empty() {
+            return Err("el archivo no tiene triángulos".to_string());
+        }
+        let bounding_sphere = mesh.bounding_sphere();
+
+        Ok(GltfModel { mesh, bounding_sphere })
+    }
+
+    // Centra el modelo en el origen y lo escala a radio envolvente 1
+    pub fn normalize(&mut self) {
+        self.mesh.normalize();
+        self.bounding_sphere = self.mesh.bounding_sphere();
+    }
+
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        self.bounding_sphere
+    }
+
+    pub fn mesh(&self) -> Mesh {
+        self.mesh.clone()
+    }
+}
+
+// Recorre la jerarquía acumulando las transformaciones de los nodos
+fn load_node(node: &gltf::Node, parent: &Mat4, buffers: &[Data], model: &mut Mesh) -> Result<(), String> {
+    let transform = parent * Mat4::from(node.transform().matrix());
+
+    if let Some(mesh) = node.mesh() {
+        load_mesh(&mesh, &transform, buffers, model)?;
+    }
+    for child in node.children() {
+        load_node(&child, &transform, buffers, model)?;
+    }
+    Ok(())
+}
+
+fn load_mesh(mesh: &gltf::Mesh, transform: &Mat4, buffers: &[Data], model: &mut Mesh) -> Result<(), String> {
+    // Las normales se transforman con la inversa traspuesta para soportar escalas no uniformes
+    let linear: Mat3 = transform.fixed_view::<3, 3>(0, 0).into();
+    let normal_matrix = linear.try_inverse().unwrap_or(Mat3::identity()).transpose();
+    let mirrored = linear.determinant() < 0.0;
+
+    for primitive in mesh.primitives() {
+        // Puntos y líneas no se pueden rasterizar como superficies
+        if !matches!(primitive.mode(), Mode::Triangles | Mode::TriangleStrip | Mode::TriangleFan) {
+            continue;
+        }
+
+        let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| data.0.as_slice()));
+        let Some(positions) = reader.read_positions() else {
+            continue;
+        };
+        let positions: Vec<Vec3> = positions
+            .map(|p| {
+                let world = transform * Vec4::new(p[0], p[1], p[2], 1.0);
+                Vec3::new(world.x, world.y, world.z)
+            })
+            .collect();
+
+        // glTF ya usa el origen de UV arriba a la izquierda, como `Obj` tras invertir v
+        let texcoords: Vec<Vec2> = reader
+            .read_tex_coords(0)
+            .map(|coords| coords.into_f32().map(|t| Vec2::new(t[0], t[1])).collect())
+            .unwrap_or_default();
+
+        let order: Vec<u32> = match reader.read_indices() {
+            Some(indices) => indices.into_u32().collect(),
+            None => (0..positions.len() as u32).collect(),
+        };
+        if let Some(&index) = order.iter().find(|&&index| index as usize >= positions.len()) {
+            return Err(format!("índice {} fuera de rango en la malla {}", index, mesh.index()));
+        }
+
+        let mut indices = triangle_list(primitive.mode(), &order);
+        // Una transformación con reflexión invierte el sentido de giro de los triángulos
+        if mirrored {
+            for tri in indices.chunks_exact_mut(3) {
+                tri.swap(1, 2);
+            }
+        }
+
+        let normals: Vec<Vec3> = reader
+            .read_normals()
+            .map(|normals| {
+                normals
+                    .map(|n| (normal_matrix * Vec3::new(n[0], n[1], n[2])).try_normalize(f32::EPSILON).unwrap_or(Vec3::new(0.0, 1.0, 0.0)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let has_normals = normals.len() == positions.len();
+
+        let [r, g, b, _] = primitive.material().pbr_metallic_roughness().base_color_factor();
+        let base_color = LinearColor::new(r, g, b).to_srgb();
+
+        let vertices = positions
+            .iter()
+            .enumerate()
+            .map(|(index, &position)| {
+                let normal = if has_normals { normals[index] } else { Vec3::new(0.0, 1.0, 0.0) };
+                let mut vertex = Vertex::new(position, normal, texcoords.get(index).cloned().unwrap_or(Vec2::new(0.0, 0.0)));
+                vertex.color = base_color;
+                vertex
+            })
+            .collect();
+
+        let mut part = Mesh::new(vertices, indices);
+        // Sin normales en el archivo se calculan desde la geometría
+        if !has_normals {
+            part.compute_normals(DEFAULT_SMOOTHING_ANGLE);
+        }
+        model.append(&part);
+    }
+    Ok(())
+}
+
+// Lista de triángulos a partir de tiras y abanicos
+fn triangle_list(mode: Mode, order: &[u32]) -> Vec<u32> {
+    match mode {
+        Mode::TriangleStrip => (2..order.len())
+            .flat_map(|i| if i % 2 == 0 { [order[i - 2], order[i - 1], order[i]] } else { [order[i - 1], order[i - 2], order[i]] })
+            .collect(),
+        Mode::TriangleFan => (2..order.len()).flat_map(|i| [order[0], order[i - 1], order[i]]).collect(),
+        _ => order[..order.len() / 3 * 3].to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Un triángulo en el plano XY (posiciones y luego índices u16), sin normales
+    const TRIANGLE_BASE64: &str = "AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAAABAAIAAAA=";
+
+    // Documento con un nodo trasladado y un material rojo; `uri` es la del único buffer
+    fn triangle_gltf(uri: &str) -> String {
+        format!(
+            r#"{{
+                "asset": {{ "version": "2.0" }},
+                "scene": 0,
+                "scenes": [{{ "nodes": [0] }}],
+                "nodes": [{{ "mesh": 0, "translation": [0.0, 0.0, 2.0] }}],
+                "meshes": [{{ "primitives": [{{ "attributes": {{ "POSITION": 0 }}, "indices": 1, "material": 0 }}] }}],
+                "materials": [{{ "pbrMetallicRoughness": {{ "baseColorFactor": [1.0, 0.0, 0.0, 1.0] }} }}],
+                "accessors": [
+                    {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0.0, 0.0, 0.0], "max": [1.0, 1.0, 0.0] }},
+                    {{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }}
+                ],
+                "bufferViews": [
+                    {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+                    {{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }}
+                ],
+                "buffers": [{{ "byteLength": 44, "uri": "{}" }}]
+            }}"#,
+            uri
+        )
+    }
+
+    fn assert_triangle(model: &GltfModel) {
+        let mesh = model.mesh();
+        assert_eq!(mesh.indices, vec![0, 1, 2]);
+        assert_eq!(mesh.vertices.len(), 3);
+        for vertex in &mesh.vertices {
+            // La traslación del nodo queda aplicada y la normal se calcula desde la cara
+            assert_eq!(vertex.position.z, 2.0);
+            assert!((vertex.normal - Vec3::new(0.0, 0.0, 1.0)).magnitude() < 1e-6);
+            assert_eq!((vertex.color.get_r(), vertex.color.get_g(), vertex.color.get_b()), (255, 0, 0));
+        }
+    }
+
+    #[test]
+    fn loads_embedded_base64_buffer() {
+        let document = triangle_gltf(&format!("data:application/octet-stream;base64,{}", TRIANGLE_BASE64));
+        let model = GltfModel::from_slice(document.as_bytes(), Path::new("")).unwrap();
+        assert_triangle(&model);
+    }
+
+    #[test]
+    fn loads_external_buffer_with_percent_encoded_uri() {
+        let directory = std::env::temp_dir().join(format!("lab4-gltf-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let mut buffer: Vec<u8> = [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0].iter().flat_map(|value| value.to_le_bytes()).collect();
+        buffer.extend([0u16, 1, 2, 0].iter().flat_map(|index| index.to_le_bytes()));
+        std::fs::write(directory.join("un triángulo.bin"), &buffer).unwrap();
+
+        let model = GltfModel::from_slice(triangle_gltf("un%20tri%C3%A1ngulo.bin").as_bytes(), &directory);
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert_triangle(&model.unwrap());
+    }
+
+    #[test]
+    fn rejects_missing_triangles() {
+        let document = r#"{ "asset": { "version": "2.0" }, "scenes": [{ "nodes": [] }] }"#;
+        assert!(GltfModel::from_slice(document.as_bytes(), Path::new("")).is_err());
+    }
+}
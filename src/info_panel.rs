@@ -0,0 +1,153 @@
+use crate::catalog::Body;
+use crate::color::{BlendMode, Color};
+use crate::font::{draw_text, text_width, ACCENT_HEIGHT, ADVANCE, LINE_HEIGHT};
+use crate::framebuffer::Framebuffer;
+
+// Todo en píxeles de la imagen de salida
+const PANEL_MARGIN: i32 = 12;
+const PANEL_PADDING: i32 = 10;
+const COLUMN_GAP: usize = 2 * ADVANCE;
+const TITLE_SCALE: usize = 2;
+const TEXT_SCALE: usize = 1;
+
+const BACKGROUND_COLOR: Color = Color::new_rgba(8, 12, 24, 191); // 75 % de opacidad
+const BORDER_COLOR: Color = Color::new(70, 90, 130);
+const TITLE_COLOR: Color = Color::new(255, 220, 150);
+const LABEL_COLOR: Color = Color::new(140, 160, 190);
+const VALUE_COLOR: Color = Color::new(235, 240, 250);
+const MARKER_COLOR: Color = Color::new(255, 220, 150);
+
+const DAYS_PER_YEAR: f32 = 365.25;
+
+// Con más satélites que estos se muestra sólo la cantidad
+const MAX_LISTED_SATELLITES: usize = 3;
+
+// Datos del cuerpo seleccionado en el frame actual. Las distancias y la velocidad se
+// calculan en unidades físicas, independientes del modo de escala.
+pub struct BodyInfo<'a> {
+    pub body: &'a Body,
+    pub satellites: Vec<&'a str>, // hijos directos en el catálogo
+    pub sun_distance_au: Option<f32>, // `None` para el Sol
+    pub camera_distance_km: f32,
+    pub orbital_speed_km_s: Option<f32>, // respecto del cuerpo padre
+}
+
+impl BodyInfo<'_> {
+    fn rows(&self) -> Vec<(&'static str, String)> {
+        let body = self.body;
+        let orbital_period = match body.orbital_period_days {
+            Some(days) if days >= 2.0 * DAYS_PER_YEAR => format!("{:.2} años", days / DAYS_PER_YEAR),
+            Some(days) => format!("{:.2} días", days),
+            None => "-".to_string(),
+        };
+        let satellites = match self.satellites.len() {
+            0 => "-".to_string(),
+            count if count > MAX_LISTED_SATELLITES => format!("{} cuerpos", count),
+            _ => self.satellites.join(", "),
+        };
+
+        vec![
+            ("Radio", format!("{:.0} km", body.radius_km)),
+            ("Masa", format!("{:.3e} kg", body.mass_kg)),
+            ("Albedo", body.albedo.map_or("-".to_string(), |albedo| format!("{:.3}", albedo))),
+            ("Órbita alrededor de", body.parent.clone().unwrap_or_else(|| "-".to_string())),
+            ("Satélites", satellites),
+            ("Período orbital", orbital_period),
+            ("Distancia al Sol", self.sun_distance_au.map_or("-".to_string(), |au| format!("{:.3} UA", au))),
+            ("Distancia a la cámara", large_km(self.camera_distance_km)),
+            ("Velocidad orbital", self.orbital_speed_km_s.map_or("-".to_string(), |speed| format!("{:.2} km/s", speed))),
+        ]
+    }
+}
+
+fn large_km(km: f32) -> String {
+    if km >= 1e6 {
+        format!("{:.3e} km", km)
+    } else {
+        format!("{:.0} km", km)
+    }
+}
+
+// Panel en la esquina superior izquierda, sobre la imagen ya tone-mapeada
+pub fn draw_info_panel(framebuffer: &mut Framebuffer, info: &BodyInfo) {
+    let rows = info.rows();
+
+    let label_width = rows.iter().map(|(label, _)| text_width(label, TEXT_SCALE)).max().unwrap_or(0);
+    let value_width = rows.iter().map(|(_, value)| text_width(value, TEXT_SCALE)).max().unwrap_or(0);
+    let title_height = LINE_HEIGHT * TITLE_SCALE;
+    let row_height = LINE_HEIGHT * TEXT_SCALE;
+
+    let content_width = text_width(&info.body.name, TITLE_SCALE).max(label_width + COLUMN_GAP * TEXT_SCALE + value_width);
+    let content_height = title_height + rows.len() * row_height;
+    let (left, top) = (PANEL_MARGIN, PANEL_MARGIN);
+    let width = content_width as i32 + 2 * PANEL_PADDING;
+    let height = content_height as i32 + 2 * PANEL_PADDING;
+
+    shade_rect(framebuffer, left, top, width, height);
+
+    // Los textos se corren hacia abajo el alto de los acentos, que se dibujan sobre la letra
+    let text_left = left + PANEL_PADDING;
+    let mut y = top + PANEL_PADDING + (ACCENT_HEIGHT * TITLE_SCALE) as i32;
+    draw_text(framebuffer, text_left, y, &info.body.name, TITLE_COLOR.to_hex(), TITLE_SCALE);
+
+    y = top + PANEL_PADDING + title_height as i32 + (ACCENT_HEIGHT * TEXT_SCALE) as i32;
+    let value_left = text_left + (label_width + COLUMN_GAP * TEXT_SCALE) as i32;
+    for (label, value) in &rows {
+        draw_text(framebuffer, text_left, y, label, LABEL_COLOR.to_hex(), TEXT_SCALE);
+        draw_text(framebuffer, value_left, y, value, VALUE_COLOR.to_hex(), TEXT_SCALE);
+        y += row_height as i32;
+    }
+}
+
+// Círculo alrededor del cuerpo seleccionado. `center` y `radius` en píxeles de salida. El
+// borde se recorre por filas y por columnas de la pantalla, así el trazo queda continuo y
+// el costo no crece con el radio cuando la cámara está muy cerca del cuerpo.
+pub fn draw_selection_marker(framebuffer: &mut Framebuffer, center: (f32, f32), radius: f32) {
+    let color = MARKER_COLOR.to_hex();
+    let (width, height) = (framebuffer.output_width, framebuffer.output_height);
+    let (right, bottom) = (width as f32 - 1.0, height as f32 - 1.0);
+
+    // Nada que dibujar si el círculo no toca la pantalla o la encierra por completo
+    let nearest = (center.0.clamp(0.0, right) - center.0).hypot(center.1.clamp(0.0, bottom) - center.1);
+    let farthest = center.0.max(right - center.0).hypot(center.1.max(bottom - center.1));
+    if nearest > radius + 1.0 || farthest < radius - 1.0 {
+        return;
+    }
+
+    let mut plot = |x: f32, y: f32| {
+        let (x, y) = (x.round(), y.round());
+        if x >= 0.0 && y >= 0.0 && (x as usize) < width && (y as usize) < height {
+            framebuffer.buffer[y as usize * width + x as usize] = color;
+        }
+    };
+    let half_chord = |offset: f32| (radius * radius - offset * offset).max(0.0).sqrt();
+
+    for y in (center.1 - radius).ceil().max(0.0) as usize..=(center.1 + radius).floor().min(bottom) as usize {
+        let dx = half_chord(y as f32 - center.1);
+        plot(center.0 - dx, y as f32);
+        plot(center.0 + dx, y as f32);
+    }
+    for x in (center.0 - radius).ceil().max(0.0) as usize..=(center.0 + radius).floor().min(right) as usize {
+        let dy = half_chord(x as f32 - center.0);
+        plot(x as f32, center.1 - dy);
+        plot(x as f32, center.1 + dy);
+    }
+}
+
+// Fondo semitransparente con borde de un píxel
+fn shade_rect(framebuffer: &mut Framebuffer, left: i32, top: i32, width: i32, height: i32) {
+    let right = (left + width).min(framebuffer.output_width as i32);
+    let bottom = (top + height).min(framebuffer.output_height as i32);
+
+    for y in top.max(0)..bottom {
+        for x in left.max(0)..right {
+            let index = y as usize * framebuffer.output_width + x as usize;
+            let border = x == left || y == top || x == left + width - 1 || y == top + height - 1;
+            framebuffer.buffer[index] = if border {
+                BORDER_COLOR.to_hex()
+            } else {
+                Color::from_hex(framebuffer.buffer[index]).blend(&BACKGROUND_COLOR, BlendMode::AlphaOver).to_hex()
+            };
+        }
+    }
+}
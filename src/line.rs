@@ -1,42 +1,193 @@
+use nalgebra_glm::{Vec3, Vec4};
 use crate::fragment::Fragment;
 use crate::vertex::Vertex;
 use crate::color::Color;
 
-pub fn line(a: &Vertex, b: &Vertex) -> Vec<Fragment> {
-    let mut fragments = Vec::new();
+// Rasteriza el segmento a-b en espacio de pantalla. El segmento se recorta primero
+// contra el viewport; la profundidad, el color y la posición se interpolan a lo largo
+// del trazo. Con `anti_aliased` se usa el algoritmo de Wu y la cobertura de cada
+// fragmento queda en `intensity`.
+pub fn line(a: &Vertex, b: &Vertex, width: usize, height: usize, anti_aliased: bool) -> Vec<Fragment> {
+    // Los centros de los píxeles están en (x + 0.5, y + 0.5), igual que en `triangle`
+    let offset = Vec3::new(0.5, 0.5, 0.0);
+    let a_px = a.transformed_position - offset;
+    let b_px = b.transformed_position - offset;
+
+    let (t0, t1) = match clip_to_viewport(&a_px, &b_px, width, height) {
+        Some(range) => range,
+        None => return Vec::new(),
+    };
+
+    let start = a_px.lerp(&b_px, t0);
+    let end = a_px.lerp(&b_px, t1);
+
+    // Interpola los atributos del segmento original para un parámetro local s en [0, 1]
+    let attributes = |s: f32| {
+        let t = t0 + (t1 - t0) * s;
+        (
+            start.z + (end.z - start.z) * s,
+            a.color.lerp(&b.color, t),
+            a.position.lerp(&b.position, t),
+        )
+    };
+
+    if anti_aliased {
+        wu_line(&start, &end, attributes)
+    } else {
+        bresenham_line(&start, &end, attributes)
+    }
+}
 
-    let start = a.transformed_position;
-    let end = b.transformed_position;
+fn bresenham_line<F>(start: &Vec3, end: &Vec3, attributes: F) -> Vec<Fragment>
+where
+    F: Fn(f32) -> (f32, Color, Vec3),
+{
+    let mut fragments = Vec::new();
 
-    let mut x0 = start.x as i32;
-    let mut y0 = start.y as i32;
-    let x1 = end.x as i32;
-    let y1 = end.y as i32;
+    let mut x0 = start.x.round() as i32;
+    let mut y0 = start.y.round() as i32;
+    let x1 = end.x.round() as i32;
+    let y1 = end.y.round() as i32;
 
     let dx = (x1 - x0).abs();
-    let dy = (y1 - y0).abs();
+    let dy = -(y1 - y0).abs();
 
     let sx = if x0 < x1 { 1 } else { -1 };
     let sy = if y0 < y1 { 1 } else { -1 };
 
-    let mut err = if dx > dy { dx / 2 } else { -dy / 2 };
+    let steps = dx.max(-dy).max(1) as f32;
+    let mut step = 0;
+    let mut err = dx + dy;
 
     loop {
-        let z = start.z + (end.z - start.z) * (x0 - start.x as i32) as f32 / (end.x - start.x) as f32;
-        fragments.push(Fragment::new(x0 as f32, y0 as f32, Color::new(255, 255, 255), z));
+        let (depth, color, position) = attributes(step as f32 / steps);
+        fragments.push(Fragment::new(x0 as f32, y0 as f32, color, depth, Vec3::zeros(), 1.0, position));
 
         if x0 == x1 && y0 == y1 { break; }
 
-        let e2 = err;
-        if e2 > -dx {
-            err -= dy;
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
             x0 += sx;
         }
-        if e2 < dy {
+        if e2 <= dx {
             err += dx;
             y0 += sy;
         }
+        step += 1;
+    }
+
+    fragments
+}
+
+fn wu_line<F>(start: &Vec3, end: &Vec3, attributes: F) -> Vec<Fragment>
+where
+    F: Fn(f32) -> (f32, Color, Vec3),
+{
+    let mut fragments = Vec::new();
+
+    let steep = (end.y - start.y).abs() > (end.x - start.x).abs();
+
+    // Trabajamos siempre sobre el eje mayor; `s` guarda el parámetro original
+    let (mut x0, mut y0, mut x1, mut y1) = if steep {
+        (start.y, start.x, end.y, end.x)
+    } else {
+        (start.x, start.y, end.x, end.y)
+    };
+    let reversed = x0 > x1;
+    if reversed {
+        std::mem::swap(&mut x0, &mut x1);
+        std::mem::swap(&mut y0, &mut y1);
+    }
+
+    let dx = x1 - x0;
+    let gradient = if dx.abs() < f32::EPSILON { 1.0 } else { (y1 - y0) / dx };
+
+    let mut plot = |major: i32, minor: i32, coverage: f32, s: f32| {
+        if coverage <= 0.0 {
+            return;
+        }
+        let s = if reversed { 1.0 - s } else { s };
+        let (depth, color, position) = attributes(s.clamp(0.0, 1.0));
+        let (x, y) = if steep { (minor, major) } else { (major, minor) };
+        fragments.push(Fragment::new(x as f32, y as f32, color, depth, Vec3::zeros(), coverage, position));
+    };
+
+    let x_start = x0.round() as i32;
+    let x_end = x1.round() as i32;
+
+    for major in x_start..=x_end {
+        let s = if dx.abs() < f32::EPSILON { 0.0 } else { (major as f32 - x0) / dx };
+        let minor = y0 + gradient * (major as f32 - x0);
+        let minor_floor = minor.floor();
+        let fraction = minor - minor_floor;
+
+        // Los extremos cubren sólo la parte del píxel que está dentro del segmento
+        let end_coverage = if major == x_start {
+            1.0 - (x0 + 0.5 - x_start as f32).clamp(0.0, 1.0)
+        } else if major == x_end {
+            (x1 + 0.5 - x_end as f32).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let end_coverage = if x_start == x_end { 1.0 } else { end_coverage };
+
+        plot(major, minor_floor as i32, (1.0 - fraction) * end_coverage, s);
+        plot(major, minor_floor as i32 + 1, fraction * end_coverage, s);
     }
 
     fragments
 }
+
+// Liang-Barsky: devuelve el intervalo [t0, t1] del segmento que cae dentro del viewport
+pub fn clip_to_viewport(a: &Vec3, b: &Vec3, width: usize, height: usize) -> Option<(f32, f32)> {
+    let max_x = width as f32 - 1.0;
+    let max_y = height as f32 - 1.0;
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+
+    let mut t0 = 0.0f32;
+    let mut t1 = 1.0f32;
+
+    for (p, q) in [(-dx, a.x), (dx, max_x - a.x), (-dy, a.y), (dy, max_y - a.y)] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else {
+            let r = q / p;
+            if p < 0.0 {
+                t0 = t0.max(r);
+            } else {
+                t1 = t1.min(r);
+            }
+            if t0 > t1 {
+                return None;
+            }
+        }
+    }
+
+    Some((t0, t1))
+}
+
+// Recorta un segmento en clip space contra el plano near (z >= -w), antes de la división
+// de perspectiva, para que los puntos detrás de la cámara no se proyecten invertidos.
+pub fn clip_to_near_plane(a: Vec4, b: Vec4) -> Option<(Vec4, Vec4, f32, f32)> {
+    let da = a.z + a.w;
+    let db = b.z + b.w;
+
+    if da < 0.0 && db < 0.0 {
+        return None;
+    }
+    if da >= 0.0 && db >= 0.0 {
+        return Some((a, b, 0.0, 1.0));
+    }
+
+    let t = da / (da - db);
+    let clipped = a + (b - a) * t;
+    if da < 0.0 {
+        Some((clipped, b, t, 1.0))
+    } else {
+        Some((a, clipped, 0.0, t))
+    }
+}
@@ -0,0 +1,253 @@
+use nalgebra_glm::{Vec2, Vec3, Vec4};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::sync::Arc;
+use crate::culling::BoundingSphere;
+use crate::mesh::Mesh;
+use crate::obj::Obj;
+use crate::vertex::Vertex;
+use crate::Uniforms;
+
+// Radio proyectado (en píxeles) a partir del cual se usa cada nivel, de más a menos detalle.
+// El nivel 1 es la malla original; el 0 es la original subdividida y los siguientes son
+// esferas generadas sobre la esfera envolvente.
+const LOD_THRESHOLDS: [f32; 4] = [220.0, 45.0, 12.0, 0.75];
+
+// Margen relativo alrededor de cada umbral para no alternar entre niveles en cada frame
+const LOD_HYSTERESIS: f32 = 0.15;
+
+// Distancia bajo la cual dos vértices de la malla original se consideran el mismo
+const WELD_EPSILON: f32 = 1e-5;
+
+pub enum Lod<'a> {
+    Mesh(&'a Mesh),
+    Impostor, // cuerpo de menos de un píxel: se dibuja como un punto
+}
+
+// Cadena de mallas de un cuerpo con el nivel elegido en el frame anterior. Las mallas se
+// comparten entre las copias; el nivel actual es propio de cada una.
+#[derive(Clone)]
+pub struct LodMesh {
+    levels: Arc<[Mesh]>,
+    bounds: BoundingSphere,
+    current: usize, // `levels.len()` significa impostor
+}
+
+impl LodMesh {
+    pub fn from_obj(obj: &Obj) -> Self {
+        // Los OBJ de los cuerpos traen una normal por cara; se recalculan suaves del todo para
+        // que la iluminación no muestre las facetas
+        let mut original = obj.mesh();
+        original.compute_normals(PI);
+        original.weld(WELD_EPSILON);
+        let bounds = obj.bounding_sphere();
+
+        let levels = vec![
+            subdivide(&original, &bounds.center),
+            original,
+            uv_sphere(&bounds, 8, 16),
+            uv_sphere(&bounds, 4, 8),
+        ];
+
+        LodMesh { levels: with_tangents(levels).into(), bounds, current: 1 }
+    }
+
+    // Esfera unitaria geodésica para cuerpos con relieve en el vertex shader: el nivel más
+    // detallado subdivide `subdivisions` veces un icosaedro y cada nivel siguiente una vez
+    // menos. A diferencia de la esfera por paralelos, los triángulos tienen tamaño parejo en
+    // toda la superficie, así que el relieve se ve igual en los polos y en el ecuador.
+    pub fn icosphere(subdivisions: u32) -> Self {
+        let mut spheres = vec![icosahedron()];
+        for _ in 0..subdivisions {
+            let mut sphere = subdivide(spheres.last().unwrap(), &Vec3::zeros());
+            spherical_tex_coords(&mut sphere);
+            spheres.push(sphere);
+        }
+
+        let levels = (0..LOD_THRESHOLDS.len())
+            .map(|level| spheres[subdivisions.saturating_sub(level as u32) as usize].clone())
+            .collect();
+        LodMesh::from_levels(levels)
+    }
+
+    // Modelo sin versiones simplificadas (p. ej. una nave): la misma malla en todos los
+    // niveles, así sólo cambia a impostor cuando queda de menos de un píxel
+    pub fn from_mesh(mesh: Mesh) -> Self {
+        LodMesh::from_levels(vec![mesh; LOD_THRESHOLDS.len()])
+    }
+
+    // Cadena ya generada, de más a menos detalle (una por cada umbral de `LOD_THRESHOLDS`)
+    pub fn from_levels(levels: Vec<Mesh>) -> Self {
+        let bounds = BoundingSphere::from_points(&levels[0].positions());
+
+        LodMesh { levels: with_tangents(levels).into(), bounds, current: 1 }
+    }
+
+    pub fn bounds(&self) -> &BoundingSphere {
+        &self.bounds
+    }
+
+    // Nivel elegido en el último `select`, `None` si el cuerpo se dibujó como impostor
+    pub fn level(&self) -> Option<usize> {
+        (self.current < self.levels.len()).then_some(self.current)
+    }
+
+    // Elige el nivel para el radio proyectado actual. Sólo se cambia de nivel cuando el
+    // radio cruza el umbral con un margen de `LOD_HYSTERESIS`.
+    pub fn select(&mut self, projected_radius: f32) -> Lod<'_> {
+        let target = LOD_THRESHOLDS
+            .iter()
+            .position(|&threshold| projected_radius >= threshold)
+            .unwrap_or(self.levels.len());
+
+        if target < self.current {
+            // Más detalle: el radio debe superar el umbral del nivel destino con margen
+            if projected_radius >= LOD_THRESHOLDS[target] * (1.0 + LOD_HYSTERESIS) {
+                self.current = target;
+            } else if target + 1 < self.current {
+                self.current = target + 1;
+            }
+        } else if target > self.current {
+            // Menos detalle: el radio debe caer por debajo del umbral actual con margen
+            if projected_radius < LOD_THRESHOLDS[self.current] * (1.0 - LOD_HYSTERESIS) {
+                self.current = target;
+            }
+        }
+
+        match self.levels.get(self.current) {
+            Some(mesh) => Lod::Mesh(mesh),
+            None => Lod::Impostor,
+        }
+    }
+}
+
+// Radio en píxeles de la esfera envolvente proyectada. Se asume escala uniforme en la
+// matriz de modelo (como en `create_model_matrix`).
+pub fn projected_radius(bounds: &BoundingSphere, uniforms: &Uniforms) -> f32 {
+    let center = uniforms.model_matrix * Vec4::new(bounds.center.x, bounds.center.y, bounds.center.z, 1.0);
+    let view_center = uniforms.view_matrix * center;
+    let scale = Vec3::new(uniforms.model_matrix[(0, 0)], uniforms.model_matrix[(1, 0)], uniforms.model_matrix[(2, 0)]).magnitude();
+
+    let distance = -view_center.z;
+    let radius = bounds.radius * scale;
+    if distance <= radius {
+        return f32::INFINITY;
+    }
+
+    // Altura de la pantalla en píxeles por unidad de distancia a profundidad 1
+    let pixels_per_unit = uniforms.projection_matrix[(1, 1)] * uniforms.viewport_matrix[(1, 1)].abs();
+    radius / distance * pixels_per_unit
+}
+
+// Tangentes de cada nivel para los mapas de normales y la vista de depuración de tangentes
+fn with_tangents(mut levels: Vec<Mesh>) -> Vec<Mesh> {
+    for level in levels.iter_mut() {
+        level.compute_tangents();
+    }
+    levels
+}
+
+// Divide cada triángulo en cuatro. Los puntos medios se proyectan a la distancia media de
+// sus extremos respecto de `center`, así la silueta se curva en lugar de quedar plana. Cada
+// arista compartida genera un solo punto medio.
+fn subdivide(mesh: &Mesh, center: &Vec3) -> Mesh {
+    let midpoint = |a: &Vertex, b: &Vertex| {
+        let distance = ((a.position - center).magnitude() + (b.position - center).magnitude()) * 0.5;
+        let direction = (a.position + b.position) * 0.5 - center;
+        let position = if direction.magnitude() > 0.0 {
+            center + direction.normalize() * distance
+        } else {
+            (a.position + b.position) * 0.5
+        };
+        let normal = (a.normal + b.normal).try_normalize(f32::EPSILON).unwrap_or(a.normal);
+        Vertex::new(position, normal, (a.tex_coords + b.tex_coords) * 0.5)
+    };
+
+    let mut vertices = mesh.vertices.clone();
+    let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+    let mut edge = |a: u32, b: u32| {
+        *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+            vertices.push(midpoint(&mesh.vertices[a as usize], &mesh.vertices[b as usize]));
+            (vertices.len() - 1) as u32
+        })
+    };
+
+    let mut indices = Vec::with_capacity(mesh.indices.len() * 4);
+    for tri in mesh.indices.chunks_exact(3) {
+        let (a, b, c) = (tri[0], tri[1], tri[2]);
+        let (ab, bc, ca) = (edge(a, b), edge(b, c), edge(c, a));
+        indices.extend([a, ab, ca, ab, b, bc, ca, bc, c, ab, bc, ca]);
+    }
+    Mesh::new(vertices, indices)
+}
+
+// Esfera por paralelos y meridianos sobre `bounds`, en orden antihorario visto desde afuera.
+// La costura y los polos repiten posiciones con distinta UV.
+fn uv_sphere(bounds: &BoundingSphere, stacks: usize, slices: usize) -> Mesh {
+    let mut vertices = Vec::with_capacity((stacks + 1) * (slices + 1));
+    for stack in 0..=stacks {
+        for slice in 0..=slices {
+            let u = slice as f32 / slices as f32;
+            let v = stack as f32 / stacks as f32;
+            let (theta, phi) = (u * 2.0 * PI, v * PI);
+            let normal = Vec3::new(phi.sin() * theta.cos(), phi.cos(), -phi.sin() * theta.sin());
+            vertices.push(Vertex::new(bounds.center + normal * bounds.radius, normal, Vec2::new(u, v)));
+        }
+    }
+
+    let index = |stack: usize, slice: usize| (stack * (slices + 1) + slice) as u32;
+    let mut indices = Vec::with_capacity(stacks * slices * 6);
+    for stack in 0..stacks {
+        for slice in 0..slices {
+            let (a, b) = (index(stack, slice), index(stack + 1, slice));
+            let (c, d) = (index(stack + 1, slice + 1), index(stack, slice + 1));
+
+            // En los polos uno de los dos triángulos es degenerado
+            if stack != 0 {
+                indices.extend([a, b, d]);
+            }
+            if stack + 1 != stacks {
+                indices.extend([b, c, d]);
+            }
+        }
+    }
+    Mesh::new(vertices, indices)
+}
+
+// Icosaedro inscripto en la esfera unitaria, en orden antihorario visto desde afuera
+fn icosahedron() -> Mesh {
+    let t = (1.0 + 5.0f32.sqrt()) * 0.5;
+    let corners = [
+        (-1.0, t, 0.0), (1.0, t, 0.0), (-1.0, -t, 0.0), (1.0, -t, 0.0),
+        (0.0, -1.0, t), (0.0, 1.0, t), (0.0, -1.0, -t), (0.0, 1.0, -t),
+        (t, 0.0, -1.0), (t, 0.0, 1.0), (-t, 0.0, -1.0), (-t, 0.0, 1.0),
+    ];
+    let vertices = corners
+        .iter()
+        .map(|&(x, y, z)| {
+            let normal = Vec3::new(x, y, z).normalize();
+            Vertex::new(normal, normal, Vec2::new(0.0, 0.0))
+        })
+        .collect();
+    let indices = vec![
+        0, 11, 5, 0, 5, 1, 0, 1, 7, 0, 7, 10, 0, 10, 11,
+        1, 5, 9, 5, 11, 4, 11, 10, 2, 10, 7, 6, 7, 1, 8,
+        3, 9, 4, 3, 4, 2, 3, 2, 6, 3, 6, 8, 3, 8, 9,
+        4, 9, 5, 2, 4, 11, 6, 2, 10, 8, 6, 7, 9, 8, 1,
+    ];
+
+    let mut mesh = Mesh::new(vertices, indices);
+    spherical_tex_coords(&mut mesh);
+    mesh
+}
+
+// UV por longitud y latitud, con la misma convención que `uv_sphere`. No hay costura: los
+// triángulos que cruzan el meridiano 0 interpolan u de un lado al otro.
+fn spherical_tex_coords(mesh: &mut Mesh) {
+    for vertex in mesh.vertices.iter_mut() {
+        let direction = vertex.position.normalize();
+        let u = (-direction.z).atan2(direction.x).rem_euclid(2.0 * PI) / (2.0 * PI);
+        let v = direction.y.clamp(-1.0, 1.0).acos() / PI;
+        vertex.tex_coords = Vec2::new(u, v);
+    }
+}
@@ -1,32 +1,113 @@
-use nalgebra_glm::{Vec3, Mat4, look_at, perspective};
-use minifb::{Key, Window, WindowOptions};
+use nalgebra_glm::{Vec3, Vec4, Mat4, look_at, perspective};
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
 use std::time::Duration;
+use std::path::PathBuf;
 use std::f32::consts::PI;
 
 mod framebuffer;
 mod triangle;
 mod vertex;
 mod obj;
+mod mesh;
+mod gltf_model;
 mod color;
 mod fragment;
 mod shaders;
 mod camera;
-
-use framebuffer::Framebuffer;
+mod line;
+mod orbit;
+mod render_mode;
+mod noise;
+mod skybox;
+mod postprocess;
+mod gbuffer;
+mod culling;
+mod lod;
+mod atmosphere;
+mod shadow;
+mod rings;
+mod ephemeris;
+mod scale;
+mod catalog;
+mod font;
+mod info_panel;
+mod trail;
+mod assets;
+mod terrain;
+mod craters;
+
+use framebuffer::{Framebuffer, AntiAliasing};
 use vertex::Vertex;
-use obj::Obj;
 use camera::Camera;
-use triangle::triangle;
-use shaders::{vertex_shader, fragment_shader, sun_shader, rocky_planet_shader,venus_shader, earth_shader, mars_shader,jupiter_shader, moon_shader, saturn_shader, saturn_rings_shader};
-
+use triangle::{triangle, is_front_facing};
+use fragment::Fragment;
+use line::{line, clip_to_near_plane};
+use orbit::Orbit;
+use color::{Color, LinearColor};
+use render_mode::{RenderMode, normal_color, tangent_color, triangle_color, depth_view};
+use skybox::Skybox;
+use postprocess::{Bloom, ToneMapping, fxaa};
+use gbuffer::GBuffer;
+use culling::{BoundingSphere, CullingStats, Frustum};
+use lod::{Lod, LodMesh, projected_radius};
+use atmosphere::Atmosphere;
+use shadow::ShadowCasters;
+use rings::ring_mesh;
+use scale::{ScaleMode, KM_PER_AU};
+use catalog::Catalog;
+use info_panel::{BodyInfo, draw_info_panel, draw_selection_marker};
+use font::{draw_text, GLYPH_HEIGHT};
+use trail::Trail;
+use assets::Assets;
+use terrain::Terrain;
+use shaders::{vertex_shader, mercury_craters, moon_craters, mars_craters, sun_shader, rocky_planet_shader,venus_shader, earth_shader, mars_shader,jupiter_shader, moon_shader, saturn_shader, saturn_rings_shader, uranus_shader, neptune_shader, station_shader};
+
+#[derive(Clone, Copy)]
 pub struct Uniforms {
     model_matrix: Mat4,
     view_matrix: Mat4,
     projection_matrix: Mat4,
     viewport_matrix: Mat4,
     time: u32,
+    camera_position: Vec3,
+    light_position: Vec3, // el Sol, fuente de luz de la escena
+    shadow_casters: ShadowCasters, // cuerpos que pueden tapar al Sol en este fragmento
+    terrain: Option<Terrain>, // relieve que el vertex shader aplica a la malla
 }
 
+const WINDOW_TITLE: &str = "Sistema Solar Shader Lab";
+
+// Paso del reloj de la simulación: un año terrestre dura unos 4500 frames
+const DAYS_PER_FRAME: f32 = 0.08;
+
+// Con ese reloj la Tierra giraría 29° por frame; los períodos de rotación más cortos que
+// este se alargan para que el giro de los cuerpos se pueda seguir
+const MIN_ROTATION_PERIOD_DAYS: f32 = 4.0;
+
+const Z_NEAR: f32 = 0.1;
+const Z_FAR: f32 = 1000.0;
+
+// Radio mínimo en píxeles alrededor de un cuerpo para seleccionarlo con el mouse, así
+// también se pueden elegir los que se ven como un punto
+const PICK_RADIUS_PIXELS: f32 = 8.0;
+
+// Puntos por estela y separación mínima entre ellos, en unidades de escena. Con la escala
+// logarítmica la Tierra deja un punto cada 2 o 3 frames y su estela cubre un cuarto de órbita.
+const TRAIL_CAPACITY: usize = 512;
+const TRAIL_SPACING: f32 = 0.05;
+
+// Estación espacial en órbita geoestacionaria sobre el ecuador de la Tierra. Su radio en
+// escena es una fracción del de la Tierra, muy exagerado para que se vea.
+const STATION_MODEL: &str = "models/station.gltf";
+const GEOSTATIONARY_RADIUS_KM: f32 = 42_164.0;
+const STATION_SIZE: f32 = 0.12;
+
+const WIREFRAME_COLOR: Color = Color::new(0, 255, 128);
+const WIREFRAME_DEPTH_BIAS: f32 = 1e-4;
+
+// Separación en píxeles entre el borde de un cuerpo y su etiqueta de nivel de detalle
+const LOD_LABEL_OFFSET: i32 = 4;
+
 fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
     // Transformación para rotación y escala
     let transform_matrix = Mat4::new(
@@ -45,6 +126,12 @@ fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
     transform_matrix * rotation_matrix
 }
 
+// Matriz de modelo que lleva el centro de la malla (`shape.center`, en espacio de objeto) a
+// `position` con el radio `radius` en unidades de escena
+fn body_model_matrix(shape: &BoundingSphere, position: Vec3, radius: f32, rotation: Vec3) -> Mat4 {
+    create_model_matrix(position, radius / shape.radius, rotation) * Mat4::new_translation(&-shape.center)
+}
+
 fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
     look_at(&eye, &center, &up)
 }
@@ -52,10 +139,7 @@ fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
 fn create_perspective_matrix(window_width: f32, window_height: f32) -> Mat4 {
     let fov = 45.0 * PI / 180.0;
     let aspect_ratio = window_width / window_height;
-    let near = 0.1;
-    let far = 1000.0;
-
-    perspective(fov, aspect_ratio, near, far)
+    perspective(fov, aspect_ratio, Z_NEAR, Z_FAR)
 }
 
 fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
@@ -67,345 +151,328 @@ fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
     )
 }
 
-fn render(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex]) {
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
+type FragmentShader = fn(&Fragment, &Uniforms) -> LinearColor;
 
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
-        }
+// Con `gbuffer` la pasada de superficies sólo rasteriza hacia el G-buffer (sombreado
+// diferido); sin él cada fragmento se sombrea al rasterizarse. Devuelve `false` si el
+// cuerpo quedó fuera del volumen de vista y no se procesó ningún vértice.
+fn render(framebuffer: &mut Framebuffer, uniforms: &Uniforms, mesh: &mut LodMesh, shader: FragmentShader, mode: RenderMode, mut gbuffer: Option<&mut GBuffer>) -> bool {
+    let mut bounds = *mesh.bounds();
+    // El relieve puede sacar vértices fuera de la esfera de la malla
+    if let Some(terrain) = &uniforms.terrain {
+        bounds.radius *= 1.0 + terrain.max_height();
     }
 
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
+    // Frustum culling con la esfera envolvente en espacio de objeto
+    let frustum = Frustum::from_matrix(&(uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix));
+    if !frustum.intersects_sphere(&bounds) {
+        return false;
     }
 
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
-
-        if x < framebuffer.width && y < framebuffer.height {
-            let shaded_color = sun_shader(&fragment, uniforms);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
+    // Nivel de detalle según el tamaño en pantalla
+    let radius = projected_radius(&bounds, uniforms);
+    let level = match mesh.select(radius) {
+        Lod::Mesh(level) => level,
+        Lod::Impostor => {
+            render_impostor(framebuffer, uniforms, &bounds, radius, shader, mode);
+            return true;
         }
-    }
-}
-
-fn render_rocky_planet(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex]) {
-    // Vertex Shader
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
-
-    // Primitive Assembly
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
+    };
+
+    // Vertex Shader: una vez por vértice distinto de la malla
+    let transformed_vertices: Vec<Vertex> = level.vertices.iter().map(|vertex| vertex_shader(vertex, uniforms)).collect();
+
+    // Primitive Assembly: cada triángulo referencia sus vértices ya transformados
+    let triangles: Vec<[&Vertex; 3]> = level
+        .triangles()
+        .map(|[a, b, c]| [&transformed_vertices[a], &transformed_vertices[b], &transformed_vertices[c]])
+        .collect();
+
+    let material = gbuffer.as_deref_mut().map(|gbuffer| gbuffer.add_material(shader, *uniforms));
+
+    // Rasterization y Fragment Processing
+    if mode.draws_surfaces() {
+        for (index, (tri, corners)) in triangles.iter().zip(level.triangles()).enumerate() {
+            for fragment in triangle(tri[0], tri[1], tri[2], framebuffer.sample_pattern()) {
+                let x = fragment.position.x as usize;
+                let y = fragment.position.y as usize;
+
+                if x >= framebuffer.width || y >= framebuffer.height {
+                    continue;
+                }
+
+                if let (Some(gbuffer), Some(material)) = (gbuffer.as_deref_mut(), material) {
+                    framebuffer.depth_samples(x, y, fragment.coverage, &fragment.sample_depths);
+                    gbuffer.write(x, y, &fragment, material);
+                } else {
+                    let shaded_color = match mode {
+                        RenderMode::Normals => normal_color(&fragment.normal).to_linear(),
+                        RenderMode::TriangleColors => triangle_color(index).to_linear(),
+                        RenderMode::Tangents => tangent_color(level.face_tangent(corners)).to_linear(),
+                        _ => shader(&fragment, uniforms),
+                    };
+                    framebuffer.set_current_color(shaded_color);
+                    framebuffer.point_samples(x, y, fragment.coverage, &fragment.sample_depths);
+                }
+            }
         }
     }
 
-    // Rasterization
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
-    }
-
-    // Fragment Processing
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
-
-        if x < framebuffer.width && y < framebuffer.height {
-            let shaded_color = rocky_planet_shader(&fragment, uniforms);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
+    // Aristas de los triángulos encima (o en lugar) de las superficies
+    if mode.draws_edges() {
+        framebuffer.set_current_color(WIREFRAME_COLOR.to_linear());
+
+        for tri in &triangles {
+            // Sobre superficies sombreadas sólo se ven las caras frontales
+            if mode == RenderMode::ShadedWireframe && !is_front_facing(tri[0], tri[1], tri[2]) {
+                continue;
+            }
+
+            for (a, b) in [(tri[0], tri[1]), (tri[1], tri[2]), (tri[2], tri[0])] {
+                for fragment in line(a, b, framebuffer.width, framebuffer.height, false) {
+                    let x = fragment.position.x as usize;
+                    let y = fragment.position.y as usize;
+                    framebuffer.blend_point(x, y, fragment.depth - WIREFRAME_DEPTH_BIAS, 1.0);
+                }
+            }
         }
     }
+
+    true
 }
-fn render_venus(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex]) {
-    // Vertex Shader
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
 
-    // Primitive Assembly
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
-        }
+// Cuerpo de menos de un píxel: un solo fragmento en el centro proyectado, con el punto
+// de la superficie que mira a la cámara y opacidad según el área que cubriría el disco
+fn render_impostor(framebuffer: &mut Framebuffer, uniforms: &Uniforms, bounds: &BoundingSphere, radius: f32, shader: FragmentShader, mode: RenderMode) {
+    let center = Vec4::new(bounds.center.x, bounds.center.y, bounds.center.z, 1.0);
+    let clip = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix * center;
+    if clip.w <= 0.0 {
+        return;
     }
 
-    // Rasterización y procesamiento de fragmentos
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
+    let screen = clip_to_screen(&clip, uniforms);
+    if screen.x < 0.0 || screen.y < 0.0 {
+        return;
     }
 
-    // Fragment Shader específico de Venus
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
+    let camera = uniforms.view_matrix.try_inverse().unwrap_or(Mat4::identity()) * Vec4::new(0.0, 0.0, 0.0, 1.0);
+    let camera_object = uniforms.model_matrix.try_inverse().unwrap_or(Mat4::identity()) * camera;
+    let towards_camera = (Vec3::new(camera_object.x, camera_object.y, camera_object.z) - bounds.center)
+        .try_normalize(f32::EPSILON)
+        .unwrap_or(Vec3::new(0.0, 0.0, 1.0));
+    let world_center = uniforms.model_matrix * center;
+    let normal = (Vec3::new(camera.x, camera.y, camera.z) - Vec3::new(world_center.x, world_center.y, world_center.z))
+        .try_normalize(f32::EPSILON)
+        .unwrap_or(Vec3::new(0.0, 0.0, 1.0));
+    let intensity = normal.dot(&Vec3::new(0.0, 0.0, 1.0)).max(0.0);
+
+    let fragment = Fragment::new(
+        screen.x,
+        screen.y,
+        Color::new(100, 100, 100) * intensity,
+        screen.z,
+        normal,
+        intensity,
+        bounds.center + towards_camera * bounds.radius,
+    );
 
-        if x < framebuffer.width && y < framebuffer.height {
-            // Aplicar el shader específico para Venus
-            let shaded_color = venus_shader(&fragment, uniforms);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
-        }
-    }
+    let color = match mode {
+        RenderMode::Normals => normal_color(&normal).to_linear(),
+        RenderMode::TriangleColors => triangle_color(0).to_linear(),
+        RenderMode::Tangents => tangent_color(None).to_linear(),
+        _ => shader(&fragment, uniforms),
+    };
+    framebuffer.set_current_color(color);
+    framebuffer.blend_point(screen.x as usize, screen.y as usize, screen.z, (PI * radius * radius).min(1.0));
 }
 
-fn render_earth(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex]) {
-    // Vertex Shader
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
+// Capa de atmósfera: una copia agrandada de la malla del planeta de la que sólo se
+// rasterizan las caras frontales. Cada fragmento integra la dispersión a lo largo del rayo
+// de vista contra las esferas analíticas del planeta y de la atmósfera, y se suma al color
+// existente con prueba de profundidad pero sin escribirla. `surface_radius` está en
+// espacio de objeto y `uniforms` son los del planeta.
+fn render_atmosphere(framebuffer: &mut Framebuffer, uniforms: &Uniforms, shell: &mut LodMesh, atmosphere: &Atmosphere, surface_radius: f32) {
+    let bounds = *shell.bounds();
+
+    // Margen para que el facetado y las irregularidades de la malla no recorten la capa
+    let shell_scale = (1.0 + atmosphere.thickness) * 1.08;
+    let shell_transform = Mat4::new_translation(&bounds.center)
+        * Mat4::new_scaling(shell_scale)
+        * Mat4::new_translation(&-bounds.center);
+    let shell_uniforms = Uniforms { model_matrix: uniforms.model_matrix * shell_transform, ..*uniforms };
+
+    let frustum = Frustum::from_matrix(&(shell_uniforms.projection_matrix * shell_uniforms.view_matrix * shell_uniforms.model_matrix));
+    if !frustum.intersects_sphere(&bounds) {
+        return;
     }
 
-    // Ensamblado de Triángulos
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
-        }
-    }
+    let level = match shell.select(projected_radius(&bounds, &shell_uniforms)) {
+        Lod::Mesh(level) => level,
+        Lod::Impostor => return,
+    };
 
-    // Rasterización y procesamiento de fragmentos
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
-    }
+    let center = uniforms.model_matrix * Vec4::new(bounds.center.x, bounds.center.y, bounds.center.z, 1.0);
+    let planet_center = Vec3::new(center.x, center.y, center.z);
+    let scale = Vec3::new(uniforms.model_matrix[(0, 0)], uniforms.model_matrix[(1, 0)], uniforms.model_matrix[(2, 0)]).magnitude();
+    let planet_radius = surface_radius * scale;
 
-    // Aplicar `earth_shader` en cada fragmento
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
+    let transformed: Vec<Vertex> = level.vertices.iter().map(|vertex| vertex_shader(vertex, &shell_uniforms)).collect();
 
-        if x < framebuffer.width && y < framebuffer.height {
-            let shaded_color = earth_shader(&fragment, uniforms);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
+    for [a, b, c] in level.triangles() {
+        let (a, b, c) = (&transformed[a], &transformed[b], &transformed[c]);
+        if !is_front_facing(a, b, c) {
+            continue;
         }
-    }
-}
-fn render_mars(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex]) {
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
 
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
-        }
-    }
+        for fragment in triangle(a, b, c, framebuffer.sample_pattern()) {
+            let x = fragment.position.x as usize;
+            let y = fragment.position.y as usize;
+            if x >= framebuffer.width || y >= framebuffer.height {
+                continue;
+            }
 
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
-    }
+            let p = fragment.vertex_position;
+            let world = shell_uniforms.model_matrix * Vec4::new(p.x, p.y, p.z, 1.0);
+            let direction = (Vec3::new(world.x, world.y, world.z) - uniforms.camera_position).normalize();
 
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
+            let color = atmosphere.scatter(&uniforms.camera_position, &direction, &planet_center, planet_radius, &uniforms.light_position);
+            if color.max_component() <= 0.0 {
+                continue;
+            }
 
-        if x < framebuffer.width && y < framebuffer.height {
-            let shaded_color = mars_shader(&fragment, uniforms);
-            let color = shaded_color.to_hex();
             framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
+            framebuffer.add_samples(x, y, fragment.coverage, &fragment.sample_depths);
         }
     }
 }
-fn render_jupiter(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex]) {
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
 
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
-        }
+// Dibuja una polilínea definida en espacio de mundo (model_matrix = identidad para órbitas)
+fn render_orbit(framebuffer: &mut Framebuffer, uniforms: &Uniforms, points: &[Vec3], color: Color, anti_aliased: bool) {
+    for segment in points.windows(2) {
+        render_segment(framebuffer, uniforms, segment[0], segment[1], color, 1.0, anti_aliased);
     }
+}
 
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
+// Estela de un cuerpo desde el punto más viejo hasta `head`, la posición actual. La
+// opacidad crece con cada segmento, así el rastro se desvanece hacia atrás.
+fn render_trail(framebuffer: &mut Framebuffer, uniforms: &Uniforms, trail: &Trail, head: Vec3, color: Color, anti_aliased: bool) {
+    if trail.is_empty() {
+        return;
     }
 
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
+    let mut points = Vec::with_capacity(trail.len() + 1);
+    points.extend(trail.points().copied());
+    points.push(head);
+    let segments = points.len().saturating_sub(1);
 
-        if x < framebuffer.width && y < framebuffer.height {
-            let shaded_color = jupiter_shader(&fragment, uniforms);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
-        }
+    for (index, segment) in points.windows(2).enumerate() {
+        let alpha = (index + 1) as f32 / segments as f32;
+        render_segment(framebuffer, uniforms, segment[0], segment[1], color, alpha, anti_aliased);
     }
 }
 
-fn render_moon(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex]) {
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
+fn render_segment(framebuffer: &mut Framebuffer, uniforms: &Uniforms, from: Vec3, to: Vec3, color: Color, alpha: f32, anti_aliased: bool) {
+    let mvp = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix;
+    let a = mvp * Vec4::new(from.x, from.y, from.z, 1.0);
+    let b = mvp * Vec4::new(to.x, to.y, to.z, 1.0);
 
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
-        }
-    }
+    // Recorte contra el plano near antes de la división de perspectiva
+    let (a, b, t0, t1) = match clip_to_near_plane(a, b) {
+        Some(clipped) => clipped,
+        None => return,
+    };
 
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
-    }
+    let mut start = Vertex::new_with_color(from.lerp(&to, t0), color);
+    let mut end = Vertex::new_with_color(from.lerp(&to, t1), color);
+    start.set_transformed(clip_to_screen(&a, uniforms), Vec3::zeros());
+    end.set_transformed(clip_to_screen(&b, uniforms), Vec3::zeros());
 
-    for fragment in fragments {
+    for fragment in line(&start, &end, framebuffer.width, framebuffer.height, anti_aliased) {
         let x = fragment.position.x as usize;
         let y = fragment.position.y as usize;
 
-        if x < framebuffer.width && y < framebuffer.height {
-            let shaded_color = moon_shader(&fragment, uniforms);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
-        }
+        framebuffer.set_current_color(fragment.color.to_linear());
+        framebuffer.blend_point(x, y, fragment.depth, fragment.intensity * alpha);
     }
 }
 
-fn render_saturn(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex]) {
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
+// Nivel de detalle de un cuerpo escrito a la derecha de su círculo en pantalla, en píxeles
+// de la imagen de salida
+fn draw_lod_label(framebuffer: &mut Framebuffer, uniforms: &Uniforms, mesh: &LodMesh, sphere: &BoundingSphere) {
+    let Some((center, radius)) = screen_circle(sphere, uniforms) else {
+        return;
+    };
+    let label = match mesh.level() {
+        Some(level) => format!("LOD {}", level),
+        None => "Impostor".to_string(),
+    };
+
+    let ssaa = framebuffer.ssaa as f32;
+    let x = ((center.x + radius) / ssaa).round() as i32 + LOD_LABEL_OFFSET;
+    let y = (center.y / ssaa).round() as i32 - (GLYPH_HEIGHT / 2) as i32;
+    draw_text(framebuffer, x, y, &label, WIREFRAME_COLOR.to_hex(), 1);
+}
 
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
-        }
-    }
+fn clip_to_screen(clip: &Vec4, uniforms: &Uniforms) -> Vec3 {
+    let ndc = Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+    let screen = uniforms.viewport_matrix * ndc;
+    Vec3::new(screen.x, screen.y, screen.z)
+}
 
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
+// Círculo que ocupa en pantalla una esfera en espacio de mundo: centro (con profundidad) y
+// radio en píxeles del buffer de render. `None` si queda detrás de la cámara.
+fn screen_circle(sphere: &BoundingSphere, uniforms: &Uniforms) -> Option<(Vec3, f32)> {
+    let clip = uniforms.projection_matrix * uniforms.view_matrix * Vec4::new(sphere.center.x, sphere.center.y, sphere.center.z, 1.0);
+    if clip.w <= 0.0 {
+        return None;
     }
+    Some((clip_to_screen(&clip, uniforms), projected_radius(sphere, uniforms)))
+}
 
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
-
-        if x < framebuffer.width && y < framebuffer.height {
-            let shaded_color = saturn_shader(&fragment, uniforms);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
-        }
-    }
+struct Args {
+    start_date: f64,
+    asset_root: PathBuf,
+    station_model: String,
 }
-fn render_saturn_rings(framebuffer: &mut Framebuffer, uniforms: &Uniforms, vertex_array: &[Vertex]) {
-    let mut transformed_vertices = Vec::with_capacity(vertex_array.len());
-    for vertex in vertex_array {
-        let transformed = vertex_shader(vertex, uniforms);
-        transformed_vertices.push(transformed);
-    }
 
-    let mut triangles = Vec::new();
-    for i in (0..transformed_vertices.len()).step_by(3) {
-        if i + 2 < transformed_vertices.len() {
-            triangles.push([
-                transformed_vertices[i].clone(),
-                transformed_vertices[i + 1].clone(),
-                transformed_vertices[i + 2].clone(),
-            ]);
+// `--date AAAA-MM-DD` fija la fecha inicial de la simulación (por defecto la de hoy),
+// `--assets DIRECTORIO` la carpeta de recursos (por defecto la que está junto al ejecutable)
+// y `--model RUTA` el modelo glTF de la estación, relativo a la carpeta de recursos
+fn parse_args() -> Args {
+    let mut args = std::env::args().skip(1);
+    let mut parsed = Args { start_date: ephemeris::today(), asset_root: Assets::default_root(), station_model: STATION_MODEL.to_string() };
+
+    while let Some(arg) = args.next() {
+        let (name, value) = match arg.split_once('=') {
+            Some((name, value)) => (name.to_string(), Some(value.to_string())),
+            None => (arg.clone(), None),
+        };
+        if !matches!(name.as_str(), "--date" | "--assets" | "--model") {
+            exit_with_usage(&format!("argumento desconocido '{}'", arg));
         }
-    }
-
-    let mut fragments = Vec::new();
-    for tri in &triangles {
-        fragments.extend(triangle(&tri[0], &tri[1], &tri[2]));
-    }
-
-    for fragment in fragments {
-        let x = fragment.position.x as usize;
-        let y = fragment.position.y as usize;
+        let Some(value) = value.or_else(|| args.next()) else {
+            exit_with_usage(&format!("falta el valor después de {}", name));
+        };
 
-        if x < framebuffer.width && y < framebuffer.height {
-            let shaded_color = saturn_rings_shader(&fragment, uniforms);
-            let color = shaded_color.to_hex();
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y, fragment.depth);
+        match name.as_str() {
+            "--date" => parsed.start_date = ephemeris::parse_date(&value).unwrap_or_else(|error| exit_with_usage(&error)),
+            "--assets" => parsed.asset_root = PathBuf::from(value),
+            _ => parsed.station_model = value,
         }
     }
-}
-
-
-
 
+    parsed
+}
 
+fn exit_with_usage(message: &str) -> ! {
+    eprintln!("{}\nuso: lab4 [--date AAAA-MM-DD] [--assets DIRECTORIO] [--model RUTA]", message);
+    std::process::exit(2);
+}
 
 fn main() {
+    let args = parse_args();
+    let start_date = args.start_date;
+
     let window_width = 800;
     let window_height = 600;
     let framebuffer_width = 800;
@@ -414,7 +481,7 @@ fn main() {
 
     let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
     let mut window = Window::new(
-        "Sistema Solar Shader Lab - Sol, Mercurio y Venus",
+        WINDOW_TITLE,
         window_width,
         window_height,
         WindowOptions::default(),
@@ -422,69 +489,177 @@ fn main() {
 
     framebuffer.set_background_color(0x000000);
 
+    // Datos físicos de todos los cuerpos
+    let catalog = Catalog::embedded();
+
+    // Cada malla se carga una sola vez; los cuerpos comparten la del planeta
+    let mut assets = Assets::new(args.asset_root);
+
     // Configuración del Sol
     let translation_sun = Vec3::new(0.0, 0.0, 0.0);
-    let scale_sun = 1.5f32;
-    let rotation_sun = Vec3::new(0.0, 0.0, 0.0);
-    let obj_sun = Obj::load("assets/models/sun.obj").expect("Failed to load sun");
-    let vertex_array_sun = obj_sun.get_vertex_array();
+    let body_sun = catalog.body("Sol");
+    let obj_sun = assets.obj("models/sun.obj").unwrap_or_else(|error| panic!("Failed to load sun: {}", error));
+    let mut lod_sun = assets.lod_mesh("models/sun.obj").unwrap_or_else(|error| panic!("Failed to load sun: {}", error));
+    let shape_sun = BoundingSphere { center: obj_sun.bounding_sphere().center, radius: obj_sun.mean_radius() };
+
+    // Los cuerpos rocosos con cráteres usan una esfera geodésica densa para que el relieve
+    // se vea en la silueta
+    let terrain_sphere = LodMesh::icosphere(5);
 
     // Configuración de Mercurio
-    let translation_mercury = Vec3::new(4.0, 0.0, 0.0); // Mercurio cerca del sol
-    let scale_mercury = 0.4f32;
-    let rotation_mercury = Vec3::new(0.0, 0.0, 0.0);
-    let obj_mercury = Obj::load("assets/models/planet.obj").expect("Failed to load planet");
-    let vertex_array_mercury = obj_mercury.get_vertex_array();
+    let orbit_mercury = Orbit::from_ephemeris(translation_sun, &ephemeris::MERCURY, start_date, DAYS_PER_FRAME);
+    let body_mercury = catalog.body("Mercurio");
+    let mut lod_mercury = terrain_sphere.clone();
+    let shape_mercury = *terrain_sphere.bounds();
+
+    // Casi sin relieve propio: lo dominan los cráteres
+    let terrain_mercury = Terrain { amplitude: 0.01, frequency: 3.0, octaves: 5, ridges: 0.0, seed: 201, craters: Some(mercury_craters()) };
 
     // Configuración de Venus
-    let translation_venus = Vec3::new(6.0, 0.0, 0.0); // Venus más lejos que Mercurio
-    let scale_venus = 0.55f32;
-    let rotation_venus = Vec3::new(0.0, 0.0, 0.0);
-    let obj_venus = Obj::load("assets/models/planet.obj").expect("Failed to load planet");
-    let vertex_array_venus = obj_venus.get_vertex_array();
+    let orbit_venus = Orbit::from_ephemeris(translation_sun, &ephemeris::VENUS, start_date, DAYS_PER_FRAME);
+    let body_venus = catalog.body("Venus");
+    let obj_venus = assets.obj("models/planet.obj").unwrap_or_else(|error| panic!("Failed to load planet: {}", error));
+    let mut lod_venus = assets.lod_mesh("models/planet.obj").unwrap_or_else(|error| panic!("Failed to load planet: {}", error));
+    let shape_venus = BoundingSphere { center: obj_venus.bounding_sphere().center, radius: obj_venus.mean_radius() };
+
+    // Atmósfera densa de CO2 con bruma de ácido sulfúrico: mucha dispersión Mie amarillenta
+    let atmosphere_venus = Atmosphere {
+        thickness: 0.12,
+        rayleigh: Vec3::new(2.0, 3.2, 5.5),
+        rayleigh_scale_height: 0.03,
+        mie: Vec3::new(6.0, 4.8, 2.6),
+        mie_scale_height: 0.025,
+        mie_g: 0.7,
+        sun_intensity: 14.0,
+    };
+    let mut lod_atmosphere_venus = lod_venus.clone();
+    let surface_radius_venus = obj_venus.mean_radius();
 
     // Configuración de la Tierra
-    let translation_earth = Vec3::new(8.0, 2.0, 8.0);
-    let scale_earth = 0.6f32;
-    let rotation_earth = Vec3::new(0.0, 0.0, 0.0);
-    let obj_earth = Obj::load("assets/models/planet.obj").expect("Failed to load planet");
-    let vertex_array_earth = obj_earth.get_vertex_array();
-
-    // Configuración de la Luna
-    let scale_moon = 0.15f32; // Más pequeña que la Tierra
-    let rotation_moon = Vec3::new(0.0, 0.0, 0.0);
-    let obj_moon = Obj::load("assets/models/planet.obj").expect("Failed to load moon");
-    let vertex_array_moon = obj_moon.get_vertex_array();
+    let orbit_earth = Orbit::from_ephemeris(translation_sun, &ephemeris::EARTH, start_date, DAYS_PER_FRAME);
+    let body_earth = catalog.body("Tierra");
+    let obj_earth = assets.obj("models/planet.obj").unwrap_or_else(|error| panic!("Failed to load planet: {}", error));
+    let mut lod_earth = assets.lod_mesh("models/planet.obj").unwrap_or_else(|error| panic!("Failed to load planet: {}", error));
+    let shape_earth = BoundingSphere { center: obj_earth.bounding_sphere().center, radius: obj_earth.mean_radius() };
+
+    // Valores terrestres con el espesor exagerado unas diez veces
+    let atmosphere_earth = Atmosphere {
+        thickness: 0.1,
+        rayleigh: Vec3::new(3.5, 8.1, 19.9),
+        rayleigh_scale_height: 0.0134,
+        mie: Vec3::new(2.0, 2.0, 2.0),
+        mie_scale_height: 0.004,
+        mie_g: 0.76,
+        sun_intensity: 16.0,
+    };
+    let mut lod_atmosphere_earth = lod_earth.clone();
+    let surface_radius_earth = obj_earth.mean_radius();
+
+    // Configuración de la estación espacial, cargada desde glTF
+    let station = assets.gltf(&args.station_model).unwrap_or_else(|error| panic!("Failed to load station: {}", error));
+    let mut lod_station = LodMesh::from_mesh(station.mesh());
+    let shape_station = station.bounding_sphere();
+
+    // Configuración de la Luna, el único satélite de la Tierra en el catálogo
+    let body_moon = catalog.children(&body_earth.name).next().cloned().expect("la Tierra tiene un satélite");
+    // Órbita en km alrededor de la Tierra, con el mes sideral como período
+    let period_moon = body_moon.orbital_period_days.expect("la Luna tiene período orbital");
+    let orbit_moon = Orbit::new(Vec3::new(0.0, 0.0, 0.0), 384_400.0, 0.0549, period_moon / DAYS_PER_FRAME)
+        .with_orientation(5.145f32.to_radians(), 0.0, 0.0);
+    let mut lod_moon = terrain_sphere.clone();
+    let shape_moon = *terrain_sphere.bounds();
+
+    // Tierras altas suaves y mares bajos, con un desnivel muy exagerado para que se vea
+    let terrain_moon = Terrain { amplitude: 0.035, frequency: 2.5, octaves: 6, ridges: 0.2, seed: 301, craters: Some(moon_craters()) };
 
     // Configuración de Marte
-    let translation_mars = Vec3::new(10.0, 0.0, 0.0); // Más lejos que la Tierra
-    let scale_mars = 0.5f32;
-    let rotation_mars = Vec3::new(0.0, 0.0, 0.0);
-    let obj_mars = Obj::load("assets/models/planet.obj").expect("Failed to load planet");
-    let vertex_array_mars = obj_mars.get_vertex_array();
+    let orbit_mars = Orbit::from_ephemeris(translation_sun, &ephemeris::MARS, start_date, DAYS_PER_FRAME);
+    let body_mars = catalog.body("Marte");
+    let mut lod_mars = terrain_sphere.clone();
+    let shape_mars = *terrain_sphere.bounds();
+
+    // Cordilleras marcadas con cuencas anchas entre ellas
+    let terrain_mars = Terrain { amplitude: 0.045, frequency: 2.0, octaves: 6, ridges: 0.6, seed: 401, craters: Some(mars_craters()) };
 
     // Configuración de Júpiter
-    let translation_jupiter = Vec3::new(15.0, 0.0, 0.0); // Más lejos que Marte
-    let scale_jupiter = 1.2f32; // Más grande, ya que es un gigante gaseoso
-    let rotation_jupiter = Vec3::new(0.0, 0.0, 0.0);
-    let obj_jupiter = Obj::load("assets/models/planet.obj").expect("Failed to load planet");
-    let vertex_array_jupiter = obj_jupiter.get_vertex_array();
+    let orbit_jupiter = Orbit::from_ephemeris(translation_sun, &ephemeris::JUPITER, start_date, DAYS_PER_FRAME);
+    let body_jupiter = catalog.body("Júpiter");
+    let obj_jupiter = assets.obj("models/planet.obj").unwrap_or_else(|error| panic!("Failed to load planet: {}", error));
+    let mut lod_jupiter = assets.lod_mesh("models/planet.obj").unwrap_or_else(|error| panic!("Failed to load planet: {}", error));
+    let shape_jupiter = BoundingSphere { center: obj_jupiter.bounding_sphere().center, radius: obj_jupiter.mean_radius() };
 
     // Configuración de Saturno
-    let translation_saturn = Vec3::new(20.0, 0.0, 0.0); // Más lejos que Júpiter
-    let scale_saturn = 1.0f32;
-    let rotation_saturn = Vec3::new(0.0, 0.0, 0.0);
-    let obj_saturn = Obj::load("assets/models/planet.obj").expect("Failed to load planet");
-    let vertex_array_saturn = obj_saturn.get_vertex_array();
+    let orbit_saturn = Orbit::from_ephemeris(translation_sun, &ephemeris::SATURN, start_date, DAYS_PER_FRAME);
+    let body_saturn = catalog.body("Saturno");
+    let obj_saturn = assets.obj("models/planet.obj").unwrap_or_else(|error| panic!("Failed to load planet: {}", error));
+    let mut lod_saturn = assets.lod_mesh("models/planet.obj").unwrap_or_else(|error| panic!("Failed to load planet: {}", error));
+    let shape_saturn = BoundingSphere { center: obj_saturn.bounding_sphere().center, radius: obj_saturn.mean_radius() };
+
+    // Anillos planos en el ecuador de Saturno, con las bandas entre 1.3 y 2.2 radios del planeta
+    let mut lod_rings = LodMesh::from_levels(vec![ring_mesh(256), ring_mesh(128), ring_mesh(48), ring_mesh(16)]);
+    let scale_rings_per_radius = 1.2f32;
+
+    // Configuración de Urano
+    let orbit_uranus = Orbit::from_ephemeris(translation_sun, &ephemeris::URANUS, start_date, DAYS_PER_FRAME);
+    let body_uranus = catalog.body("Urano");
+    let obj_uranus = assets.obj("models/planet.obj").unwrap_or_else(|error| panic!("Failed to load planet: {}", error));
+    let mut lod_uranus = assets.lod_mesh("models/planet.obj").unwrap_or_else(|error| panic!("Failed to load planet: {}", error));
+    let shape_uranus = BoundingSphere { center: obj_uranus.bounding_sphere().center, radius: obj_uranus.mean_radius() };
+
+    // Configuración de Neptuno
+    let orbit_neptune = Orbit::from_ephemeris(translation_sun, &ephemeris::NEPTUNE, start_date, DAYS_PER_FRAME);
+    let body_neptune = catalog.body("Neptuno");
+    let obj_neptune = assets.obj("models/planet.obj").unwrap_or_else(|error| panic!("Failed to load planet: {}", error));
+    let mut lod_neptune = assets.lod_mesh("models/planet.obj").unwrap_or_else(|error| panic!("Failed to load planet: {}", error));
+    let shape_neptune = BoundingSphere { center: obj_neptune.bounding_sphere().center, radius: obj_neptune.mean_radius() };
+
+    let skybox = Skybox::new(4000, 2024);
+    let bloom = Bloom::new(1.0, 8);
 
     // Cámara inicial
     let mut camera = Camera::new(
-        Vec3::new(0.0, 0.0, 10.0),
+        Vec3::new(0.0, 12.0, 40.0),
         Vec3::new(0.0, 0.0, 0.0),
         Vec3::new(0.0, 1.0, 0.0)
     );
 
     let mut time = 0;
+    let mut render_mode = RenderMode::Shaded;
+    let mut show_sky = true;
+    let mut bloom_enabled = true;
+    let mut anti_aliasing = AntiAliasing::None;
+    let mut tone_mapping = ToneMapping::AcesFilmic;
+    let mut exposure = 1.0f32;
+    let mut show_orbits = true;
+    let mut show_trails = true;
+    let mut anti_aliased_orbits = true;
+    let mut show_atmospheres = true;
+    let mut show_shadows = true;
+    let mut show_terrain = true;
+    let mut scale_mode = ScaleMode::Logarithmic;
+    let mut deferred_shading = false;
+    let mut last_stats = CullingStats::default();
+    let mut last_date = String::new();
+    let mut selected_body: Option<usize> = None;
+    let mut mouse_was_down = false;
+    let mut gbuffer = GBuffer::new(framebuffer.width, framebuffer.height, framebuffer.samples);
+    let orbit_color = Color::new(70, 90, 130);
+    let orbit_segments = 180;
+
+    // Estelas de Mercurio, Venus, la Tierra, la Luna, Marte, Júpiter, Saturno, Urano y
+    // Neptuno, en ese orden
+    let mut trails: Vec<Trail> = (0..9).map(|_| Trail::new(TRAIL_CAPACITY, TRAIL_SPACING)).collect();
+    let trail_colors = [
+        Color::new(150, 150, 150),
+        Color::new(220, 200, 140),
+        Color::new(90, 150, 255),
+        Color::new(210, 210, 210),
+        Color::new(230, 110, 70),
+        Color::new(220, 170, 120),
+        Color::new(230, 200, 120),
+        Color::new(160, 220, 230),
+        Color::new(80, 120, 240),
+    ];
 
     while window.is_open() {
         if window.is_key_down(Key::Escape) {
@@ -493,121 +668,492 @@ fn main() {
 
         time += 1;
 
-        // Configuración de la órbita de la Luna alrededor de la Tierra
-        let orbit_radius = 2.0; 
-        let moon_x = translation_earth.x + orbit_radius * (time as f32 * 0.01).cos();
-        let moon_y = translation_earth.y + 2.0  + orbit_radius * (time as f32 * 0.01).sin();
-        let moon_z = translation_earth.z;
-        let translation_moon = Vec3::new(moon_x, moon_y, moon_z);
+        // Fecha simulada: avanza con el mismo reloj que mueve las órbitas
+        let date = ephemeris::format_date(start_date + time as f64 * DAYS_PER_FRAME as f64);
+
+        // Posiciones heliocéntricas en UA del frame actual
+        let position_mercury = orbit_mercury.position_at(time as f32);
+        let position_venus = orbit_venus.position_at(time as f32);
+        let position_earth = orbit_earth.position_at(time as f32);
+        let position_mars = orbit_mars.position_at(time as f32);
+        let position_jupiter = orbit_jupiter.position_at(time as f32);
+        let position_saturn = orbit_saturn.position_at(time as f32);
+        let position_uranus = orbit_uranus.position_at(time as f32);
+        let position_neptune = orbit_neptune.position_at(time as f32);
+        let offset_moon = orbit_moon.position_at(time as f32); // en km respecto de la Tierra
+
+        let translation_mercury = scale_mode.position(position_mercury);
+        let translation_venus = scale_mode.position(position_venus);
+        let translation_earth = scale_mode.position(position_earth);
+        let translation_mars = scale_mode.position(position_mars);
+        let translation_jupiter = scale_mode.position(position_jupiter);
+        let translation_saturn = scale_mode.position(position_saturn);
+        let translation_uranus = scale_mode.position(position_uranus);
+        let translation_neptune = scale_mode.position(position_neptune);
+
+        // La Luna orbita alrededor de la posición actual de la Tierra
+        let translation_moon = translation_earth + scale_mode.satellite_offset(offset_moon, body_earth.radius_km);
 
         // Procesar entrada de la cámara
         handle_input(&window, &mut camera);
 
+        let selected_mode = RenderMode::from_input(&window, render_mode);
+        let mut title_changed = selected_mode != render_mode;
+        render_mode = selected_mode;
+
+        if window.is_key_pressed(Key::M, KeyRepeat::No) {
+            anti_aliasing = anti_aliasing.next();
+            framebuffer.set_anti_aliasing(anti_aliasing);
+            title_changed = true;
+        }
+        if window.is_key_pressed(Key::X, KeyRepeat::No) {
+            scale_mode = scale_mode.next();
+            title_changed = true;
+
+            // Las estelas guardan posiciones de escena, que dejan de valer con otra escala
+            for trail in trails.iter_mut() {
+                trail.clear();
+            }
+        }
+        if window.is_key_pressed(Key::G, KeyRepeat::No) {
+            deferred_shading = !deferred_shading;
+            title_changed = true;
+        }
+        if window.is_key_pressed(Key::T, KeyRepeat::No) {
+            tone_mapping = tone_mapping.next();
+            title_changed = true;
+        }
+        if window.is_key_down(Key::Equal) {
+            exposure = (exposure * 1.02).min(16.0);
+            title_changed = true;
+        }
+        if window.is_key_down(Key::Minus) {
+            exposure = (exposure / 1.02).max(1.0 / 16.0);
+            title_changed = true;
+        }
+
+        if window.is_key_pressed(Key::B, KeyRepeat::No) {
+            bloom_enabled = !bloom_enabled;
+        }
+        if window.is_key_pressed(Key::K, KeyRepeat::No) {
+            show_sky = !show_sky;
+        }
+        if window.is_key_pressed(Key::H, KeyRepeat::No) {
+            show_atmospheres = !show_atmospheres;
+        }
+        if window.is_key_pressed(Key::Y, KeyRepeat::No) {
+            show_shadows = !show_shadows;
+        }
+        if window.is_key_pressed(Key::V, KeyRepeat::No) {
+            show_terrain = !show_terrain;
+        }
+        if window.is_key_pressed(Key::O, KeyRepeat::No) {
+            show_orbits = !show_orbits;
+        }
+        if window.is_key_pressed(Key::R, KeyRepeat::No) {
+            show_trails = !show_trails;
+        }
+        if window.is_key_pressed(Key::L, KeyRepeat::No) {
+            anti_aliased_orbits = !anti_aliased_orbits;
+        }
+
         // Actualizar la matriz de vista de la cámara
         let view_matrix = create_view_matrix(camera.eye, camera.center, camera.up);
 
         framebuffer.clear();
 
+        // Uniforms para geometría definida directamente en espacio de mundo
+        let uniforms_world = Uniforms {
+            model_matrix: Mat4::identity(),
+            view_matrix,
+            projection_matrix: create_perspective_matrix(window_width as f32, window_height as f32),
+            viewport_matrix: create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32),
+            time,
+            camera_position: camera.eye,
+            light_position: translation_sun,
+            shadow_casters: ShadowCasters::none(),
+            terrain: None,
+        };
+
+        // Fondo de estrellas y Vía Láctea
+        if show_sky {
+            skybox.render(&mut framebuffer, &uniforms_world);
+        }
+
+        // El camino diferido sólo aplica a las vistas sin aristas ni colores de depuración
+        let use_deferred = deferred_shading && matches!(render_mode, RenderMode::Shaded | RenderMode::Depth);
+        if use_deferred {
+            gbuffer.clear(&framebuffer);
+        }
+        let mut deferred_target = use_deferred.then_some(&mut gbuffer);
+        let mut stats = CullingStats::default();
+
+        // Giro e inclinación de cada cuerpo según su período de rotación
+        let days = time as f32 * DAYS_PER_FRAME;
+        let rotation_sun = body_sun.rotation(days, MIN_ROTATION_PERIOD_DAYS);
+        let rotation_mercury = body_mercury.rotation(days, MIN_ROTATION_PERIOD_DAYS);
+        let rotation_venus = body_venus.rotation(days, MIN_ROTATION_PERIOD_DAYS);
+        let rotation_earth = body_earth.rotation(days, MIN_ROTATION_PERIOD_DAYS);
+        let rotation_moon = body_moon.rotation(days, MIN_ROTATION_PERIOD_DAYS);
+        let rotation_mars = body_mars.rotation(days, MIN_ROTATION_PERIOD_DAYS);
+        let rotation_jupiter = body_jupiter.rotation(days, MIN_ROTATION_PERIOD_DAYS);
+        let rotation_saturn = body_saturn.rotation(days, MIN_ROTATION_PERIOD_DAYS);
+        let rotation_uranus = body_uranus.rotation(days, MIN_ROTATION_PERIOD_DAYS);
+        let rotation_neptune = body_neptune.rotation(days, MIN_ROTATION_PERIOD_DAYS);
+
+        // Matrices de modelo del frame, necesarias antes de dibujar para calcular las sombras
+        let model_matrix_sun = body_model_matrix(&shape_sun, translation_sun, scale_mode.radius(body_sun.radius_km), rotation_sun);
+        let model_matrix_mercury = body_model_matrix(&shape_mercury, translation_mercury, scale_mode.radius(body_mercury.radius_km), rotation_mercury);
+        let model_matrix_venus = body_model_matrix(&shape_venus, translation_venus, scale_mode.radius(body_venus.radius_km), rotation_venus);
+        let model_matrix_earth = body_model_matrix(&shape_earth, translation_earth, scale_mode.radius(body_earth.radius_km), rotation_earth);
+
+        // La estación gira con la Tierra, así queda siempre sobre el mismo punto del ecuador
+        let offset_station = create_model_matrix(Vec3::zeros(), GEOSTATIONARY_RADIUS_KM, rotation_earth) * Vec4::new(1.0, 0.0, 0.0, 0.0);
+        let translation_station = translation_earth + scale_mode.satellite_offset(offset_station.xyz(), body_earth.radius_km);
+        let model_matrix_station = body_model_matrix(&shape_station, translation_station, scale_mode.radius(body_earth.radius_km) * STATION_SIZE, rotation_earth);
+        let model_matrix_moon = body_model_matrix(&shape_moon, translation_moon, scale_mode.radius(body_moon.radius_km), rotation_moon);
+        let model_matrix_mars = body_model_matrix(&shape_mars, translation_mars, scale_mode.radius(body_mars.radius_km), rotation_mars);
+        let model_matrix_jupiter = body_model_matrix(&shape_jupiter, translation_jupiter, scale_mode.radius(body_jupiter.radius_km), rotation_jupiter);
+        let model_matrix_saturn = body_model_matrix(&shape_saturn, translation_saturn, scale_mode.radius(body_saturn.radius_km), rotation_saturn);
+        let model_matrix_uranus = body_model_matrix(&shape_uranus, translation_uranus, scale_mode.radius(body_uranus.radius_km), rotation_uranus);
+        let model_matrix_neptune = body_model_matrix(&shape_neptune, translation_neptune, scale_mode.radius(body_neptune.radius_km), rotation_neptune);
+
+        // Esferas en espacio de mundo de cada cuerpo
+        let sphere_sun = shape_sun.transform(&model_matrix_sun);
+        let sphere_mercury = shape_mercury.transform(&model_matrix_mercury);
+        let sphere_venus = shape_venus.transform(&model_matrix_venus);
+        let sphere_earth = shape_earth.transform(&model_matrix_earth);
+        let sphere_station = shape_station.transform(&model_matrix_station);
+        let sphere_moon = shape_moon.transform(&model_matrix_moon);
+        let sphere_mars = shape_mars.transform(&model_matrix_mars);
+        let sphere_jupiter = shape_jupiter.transform(&model_matrix_jupiter);
+        let sphere_saturn = shape_saturn.transform(&model_matrix_saturn);
+        let sphere_uranus = shape_uranus.transform(&model_matrix_uranus);
+        let sphere_neptune = shape_neptune.transform(&model_matrix_neptune);
+
+        let mut shadow_casters = ShadowCasters::new(scale_mode.radius(body_sun.radius_km));
+        if show_shadows {
+            for sphere in [sphere_mercury, sphere_venus, sphere_earth, sphere_moon, sphere_mars, sphere_jupiter, sphere_saturn, sphere_uranus, sphere_neptune] {
+                shadow_casters.add(sphere);
+            }
+        }
+
+        // Cuerpos seleccionables: esfera en la escena, distancia al Sol en UA y rapidez
+        // orbital respecto del padre (la de la Luna es respecto de la Tierra)
+        let selectable_bodies = [
+            (&body_sun, sphere_sun, None, None),
+            (&body_mercury, sphere_mercury, Some(position_mercury.magnitude()), Some(orbit_mercury.speed_at(time as f32, body_sun.mass_kg + body_mercury.mass_kg, KM_PER_AU as f64))),
+            (&body_venus, sphere_venus, Some(position_venus.magnitude()), Some(orbit_venus.speed_at(time as f32, body_sun.mass_kg + body_venus.mass_kg, KM_PER_AU as f64))),
+            (&body_earth, sphere_earth, Some(position_earth.magnitude()), Some(orbit_earth.speed_at(time as f32, body_sun.mass_kg + body_earth.mass_kg, KM_PER_AU as f64))),
+            (&body_moon, sphere_moon, Some((position_earth + offset_moon / KM_PER_AU).magnitude()), Some(orbit_moon.speed_at(time as f32, body_earth.mass_kg + body_moon.mass_kg, 1.0))),
+            (&body_mars, sphere_mars, Some(position_mars.magnitude()), Some(orbit_mars.speed_at(time as f32, body_sun.mass_kg + body_mars.mass_kg, KM_PER_AU as f64))),
+            (&body_jupiter, sphere_jupiter, Some(position_jupiter.magnitude()), Some(orbit_jupiter.speed_at(time as f32, body_sun.mass_kg + body_jupiter.mass_kg, KM_PER_AU as f64))),
+            (&body_saturn, sphere_saturn, Some(position_saturn.magnitude()), Some(orbit_saturn.speed_at(time as f32, body_sun.mass_kg + body_saturn.mass_kg, KM_PER_AU as f64))),
+            (&body_uranus, sphere_uranus, Some(position_uranus.magnitude()), Some(orbit_uranus.speed_at(time as f32, body_sun.mass_kg + body_uranus.mass_kg, KM_PER_AU as f64))),
+            (&body_neptune, sphere_neptune, Some(position_neptune.magnitude()), Some(orbit_neptune.speed_at(time as f32, body_sun.mass_kg + body_neptune.mass_kg, KM_PER_AU as f64))),
+        ];
+
+        // Selección: Tab recorre los cuerpos, I cierra el panel y un clic elige el cuerpo
+        // más cercano a la cámara bajo el puntero
+        if window.is_key_pressed(Key::Tab, KeyRepeat::No) {
+            selected_body = Some(selected_body.map_or(0, |index| (index + 1) % selectable_bodies.len()));
+        }
+        if window.is_key_pressed(Key::I, KeyRepeat::No) {
+            selected_body = None;
+        }
+        let mouse_down = window.get_mouse_down(MouseButton::Left);
+        if mouse_down && !mouse_was_down {
+            if let Some((mouse_x, mouse_y)) = window.get_mouse_pos(MouseMode::Discard) {
+                // El puntero está en píxeles de la ventana; el render puede ser mayor con SSAA
+                let ssaa = framebuffer.ssaa as f32;
+                let picked = selectable_bodies
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, (_, sphere, _, _))| {
+                        let (center, radius) = screen_circle(sphere, &uniforms_world)?;
+                        let distance = (center.x / ssaa - mouse_x).hypot(center.y / ssaa - mouse_y);
+                        (distance <= (radius / ssaa).max(PICK_RADIUS_PIXELS)).then_some((index, center.z))
+                    })
+                    .min_by(|a, b| a.1.total_cmp(&b.1));
+                if let Some((index, _)) = picked {
+                    selected_body = Some(index);
+                }
+            }
+        }
+        mouse_was_down = mouse_down;
+
         // Renderizar el Sol
-        let model_matrix_sun = create_model_matrix(translation_sun, scale_sun, rotation_sun);
         let uniforms_sun = Uniforms { 
             model_matrix: model_matrix_sun, 
-            view_matrix: view_matrix.clone(), 
+            view_matrix, 
             projection_matrix: create_perspective_matrix(window_width as f32, window_height as f32),
-            viewport_matrix: create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32),
-            time
+            viewport_matrix: create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32),
+            time,
+            camera_position: camera.eye,
+            light_position: translation_sun,
+            shadow_casters: ShadowCasters::none(),
+            terrain: None,
         };
-        render(&mut framebuffer, &uniforms_sun, &vertex_array_sun);
+        stats.record(render(&mut framebuffer, &uniforms_sun, &mut lod_sun, sun_shader, render_mode, deferred_target.as_deref_mut()));
 
         // Renderizar Mercurio con el shader de planeta rocoso
-        let model_matrix_mercury = create_model_matrix(translation_mercury, scale_mercury, rotation_mercury);
         let uniforms_mercury = Uniforms {
             model_matrix: model_matrix_mercury,
-            view_matrix: view_matrix.clone(),
+            view_matrix,
             projection_matrix: create_perspective_matrix(window_width as f32, window_height as f32),
-            viewport_matrix: create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32),
+            viewport_matrix: create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32),
             time,
+            camera_position: camera.eye,
+            light_position: translation_sun,
+            shadow_casters: shadow_casters.without(&sphere_mercury),
+            terrain: show_terrain.then_some(terrain_mercury),
         };
-        render_rocky_planet(&mut framebuffer, &uniforms_mercury, &vertex_array_mercury);
+        stats.record(render(&mut framebuffer, &uniforms_mercury, &mut lod_mercury, rocky_planet_shader, render_mode, deferred_target.as_deref_mut()));
 
         // Renderizar Venus con el shader atmosférico
-        let model_matrix_venus = create_model_matrix(translation_venus, scale_venus, rotation_venus);
         let uniforms_venus = Uniforms {
             model_matrix: model_matrix_venus,
-            view_matrix: view_matrix.clone(),
+            view_matrix,
             projection_matrix: create_perspective_matrix(window_width as f32, window_height as f32),
-            viewport_matrix: create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32),
+            viewport_matrix: create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32),
             time,
+            camera_position: camera.eye,
+            light_position: translation_sun,
+            shadow_casters: shadow_casters.without(&sphere_venus),
+            terrain: None,
         };
-        render_venus(&mut framebuffer, &uniforms_venus, &vertex_array_venus);
+        stats.record(render(&mut framebuffer, &uniforms_venus, &mut lod_venus, venus_shader, render_mode, deferred_target.as_deref_mut()));
 
         // Renderizar la Tierra
-        let model_matrix_earth = create_model_matrix(translation_earth, scale_earth, rotation_earth);
         let uniforms_earth = Uniforms {
             model_matrix: model_matrix_earth,
-            view_matrix: view_matrix.clone(),
+            view_matrix,
             projection_matrix: create_perspective_matrix(window_width as f32, window_height as f32),
-            viewport_matrix: create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32),
+            viewport_matrix: create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32),
             time,
+            camera_position: camera.eye,
+            light_position: translation_sun,
+            shadow_casters: shadow_casters.without(&sphere_earth),
+            terrain: None,
         };
-        render_earth(&mut framebuffer, &uniforms_earth, &vertex_array_earth);
+        stats.record(render(&mut framebuffer, &uniforms_earth, &mut lod_earth, earth_shader, render_mode, deferred_target.as_deref_mut()));
 
         // Renderizar la Luna en órbita
-        let model_matrix_moon = create_model_matrix(translation_moon, scale_moon, rotation_moon);
         let uniforms_moon = Uniforms {
             model_matrix: model_matrix_moon,
-            view_matrix: view_matrix.clone(),
+            view_matrix,
             projection_matrix: create_perspective_matrix(window_width as f32, window_height as f32),
-            viewport_matrix: create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32),
+            viewport_matrix: create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32),
             time,
+            camera_position: camera.eye,
+            light_position: translation_sun,
+            shadow_casters: shadow_casters.without(&sphere_moon),
+            terrain: show_terrain.then_some(terrain_moon),
         };
-        render_moon(&mut framebuffer, &uniforms_moon, &vertex_array_moon);
+        stats.record(render(&mut framebuffer, &uniforms_moon, &mut lod_moon, moon_shader, render_mode, deferred_target.as_deref_mut()));
+
+        // Renderizar la estación, que entra en la sombra de la Tierra
+        let uniforms_station = Uniforms {
+            model_matrix: model_matrix_station,
+            view_matrix,
+            projection_matrix: create_perspective_matrix(window_width as f32, window_height as f32),
+            viewport_matrix: create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32),
+            time,
+            camera_position: camera.eye,
+            light_position: translation_sun,
+            shadow_casters,
+            terrain: None,
+        };
+        stats.record(render(&mut framebuffer, &uniforms_station, &mut lod_station, station_shader, render_mode, deferred_target.as_deref_mut()));
 
         // Renderizar Marte
-        let model_matrix_mars = create_model_matrix(translation_mars, scale_mars, rotation_mars);
         let uniforms_mars = Uniforms {
             model_matrix: model_matrix_mars,
-            view_matrix: view_matrix.clone(),
+            view_matrix,
             projection_matrix: create_perspective_matrix(window_width as f32, window_height as f32),
-            viewport_matrix: create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32),
+            viewport_matrix: create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32),
             time,
+            camera_position: camera.eye,
+            light_position: translation_sun,
+            shadow_casters: shadow_casters.without(&sphere_mars),
+            terrain: show_terrain.then_some(terrain_mars),
         };
-        render_mars(&mut framebuffer, &uniforms_mars, &vertex_array_mars);
+        stats.record(render(&mut framebuffer, &uniforms_mars, &mut lod_mars, mars_shader, render_mode, deferred_target.as_deref_mut()));
 
         // Renderizar Júpiter
-        let model_matrix_jupiter = create_model_matrix(translation_jupiter, scale_jupiter, rotation_jupiter);
         let uniforms_jupiter = Uniforms {
             model_matrix: model_matrix_jupiter,
-            view_matrix: view_matrix.clone(),
+            view_matrix,
             projection_matrix: create_perspective_matrix(window_width as f32, window_height as f32),
-            viewport_matrix: create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32),
+            viewport_matrix: create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32),
             time,
+            camera_position: camera.eye,
+            light_position: translation_sun,
+            shadow_casters: shadow_casters.without(&sphere_jupiter),
+            terrain: None,
         };
-        render_jupiter(&mut framebuffer, &uniforms_jupiter, &vertex_array_jupiter);
+        stats.record(render(&mut framebuffer, &uniforms_jupiter, &mut lod_jupiter, jupiter_shader, render_mode, deferred_target.as_deref_mut()));
 
         // Renderizar Saturno
-        let model_matrix_saturn = create_model_matrix(translation_saturn, scale_saturn, rotation_saturn);
         let uniforms_saturn = Uniforms {
             model_matrix: model_matrix_saturn,
-            view_matrix: view_matrix.clone(),
+            view_matrix,
             projection_matrix: create_perspective_matrix(window_width as f32, window_height as f32),
-            viewport_matrix: create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32),
+            viewport_matrix: create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32),
             time,
+            camera_position: camera.eye,
+            light_position: translation_sun,
+            shadow_casters: shadow_casters.without(&sphere_saturn),
+            terrain: None,
         };
-        render_saturn(&mut framebuffer, &uniforms_saturn, &vertex_array_saturn);
+        stats.record(render(&mut framebuffer, &uniforms_saturn, &mut lod_saturn, saturn_shader, render_mode, deferred_target.as_deref_mut()));
 
-        // Renderizar los Anillos de Saturno usando shaders
-        let scale_rings = 2.0f32; // Más grande que el planeta para simular anillos
-        let model_matrix_rings = create_model_matrix(translation_saturn, scale_rings, rotation_saturn);
+        // Renderizar los Anillos de Saturno, que reciben la sombra del planeta
+        let scale_rings = sphere_saturn.radius * scale_rings_per_radius;
+        let model_matrix_rings = create_model_matrix(sphere_saturn.center, scale_rings, rotation_saturn);
         let uniforms_rings = Uniforms {
             model_matrix: model_matrix_rings,
-            view_matrix: view_matrix.clone(),
+            view_matrix,
+            projection_matrix: create_perspective_matrix(window_width as f32, window_height as f32),
+            viewport_matrix: create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32),
+            time,
+            camera_position: camera.eye,
+            light_position: translation_sun,
+            shadow_casters,
+            terrain: None,
+        };
+        stats.record(render(&mut framebuffer, &uniforms_rings, &mut lod_rings, saturn_rings_shader, render_mode, deferred_target.as_deref_mut()));
+
+        // Renderizar Urano, casi acostado sobre su órbita por la inclinación del eje
+        let uniforms_uranus = Uniforms {
+            model_matrix: model_matrix_uranus,
+            view_matrix,
             projection_matrix: create_perspective_matrix(window_width as f32, window_height as f32),
-            viewport_matrix: create_viewport_matrix(framebuffer_width as f32, framebuffer_height as f32),
+            viewport_matrix: create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32),
             time,
+            camera_position: camera.eye,
+            light_position: translation_sun,
+            shadow_casters: shadow_casters.without(&sphere_uranus),
+            terrain: None,
         };
-        render_saturn_rings(&mut framebuffer, &uniforms_rings, &vertex_array_saturn);
-        
+        stats.record(render(&mut framebuffer, &uniforms_uranus, &mut lod_uranus, uranus_shader, render_mode, deferred_target.as_deref_mut()));
+
+        // Renderizar Neptuno
+        let uniforms_neptune = Uniforms {
+            model_matrix: model_matrix_neptune,
+            view_matrix,
+            projection_matrix: create_perspective_matrix(window_width as f32, window_height as f32),
+            viewport_matrix: create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32),
+            time,
+            camera_position: camera.eye,
+            light_position: translation_sun,
+            shadow_casters: shadow_casters.without(&sphere_neptune),
+            terrain: None,
+        };
+        stats.record(render(&mut framebuffer, &uniforms_neptune, &mut lod_neptune, neptune_shader, render_mode, deferred_target.as_deref_mut()));
+
+        // Sombreado diferido: una invocación del shader por píxel visible
+        if let Some(gbuffer) = deferred_target {
+            gbuffer.shade(&mut framebuffer);
+        }
+
+        // Atmósferas encima de las superficies ya sombreadas (también en modo diferido)
+        if show_atmospheres && render_mode.draws_surfaces() && !render_mode.is_debug_view() {
+            render_atmosphere(&mut framebuffer, &uniforms_venus, &mut lod_atmosphere_venus, &atmosphere_venus, surface_radius_venus);
+            render_atmosphere(&mut framebuffer, &uniforms_earth, &mut lod_atmosphere_earth, &atmosphere_earth, surface_radius_earth);
+        }
+
+        if title_changed || stats != last_stats || date != last_date {
+            window.set_title(&format!(
+                "{} - {} - {} - {} - {} - {} - {} (exposición {:.2}) - {} cuerpos dibujados, {} descartados",
+                WINDOW_TITLE, date, scale_mode.name(), render_mode.name(), if deferred_shading { "Diferido" } else { "Directo" },
+                anti_aliasing.name(), tone_mapping.name(), exposure,
+                stats.drawn, stats.culled
+            ));
+        }
+        last_stats = stats;
+        last_date = date;
+
+        // Órbitas en espacio de mundo, con prueba de profundidad contra los cuerpos
+        if show_orbits {
+            // Cada punto pasa por la misma transformación de escala que los cuerpos
+            for orbit in [&orbit_mercury, &orbit_venus, &orbit_earth, &orbit_mars, &orbit_jupiter, &orbit_saturn, &orbit_uranus, &orbit_neptune] {
+                let points: Vec<Vec3> = orbit.points(orbit_segments).into_iter().map(|point| scale_mode.position(point)).collect();
+                render_orbit(&mut framebuffer, &uniforms_world, &points, orbit_color, anti_aliased_orbits);
+            }
+
+            let points_moon: Vec<Vec3> = orbit_moon.points(orbit_segments)
+                .into_iter()
+                .map(|point| translation_earth + scale_mode.satellite_offset(point, body_earth.radius_km))
+                .collect();
+            render_orbit(&mut framebuffer, &uniforms_world, &points_moon, orbit_color, anti_aliased_orbits);
+        }
+
+        // Estelas con las posiciones reales recorridas; la de la Luna muestra su camino
+        // alrededor del Sol y no sólo alrededor de la Tierra
+        let translations = [translation_mercury, translation_venus, translation_earth, translation_moon, translation_mars, translation_jupiter, translation_saturn, translation_uranus, translation_neptune];
+        for ((trail, translation), color) in trails.iter_mut().zip(translations).zip(trail_colors) {
+            if show_trails {
+                render_trail(&mut framebuffer, &uniforms_world, trail, translation, color, anti_aliased_orbits);
+            }
+            trail.push(translation);
+        }
+
+
+        // Combina las muestras MSAA antes del post-procesado
+        framebuffer.resolve_samples();
+
+        // Post-procesado sobre el buffer HDR
+        if bloom_enabled && !render_mode.is_debug_view() {
+            bloom.apply(&mut framebuffer);
+        }
+
+        // Las vistas de depuración se muestran sin tone mapping para no alterar sus colores
+        if render_mode.is_debug_view() {
+            framebuffer.resolve(ToneMapping::None, 1.0);
+        } else {
+            framebuffer.resolve(tone_mapping, exposure);
+        }
+
+        if render_mode == RenderMode::Depth {
+            depth_view(&mut framebuffer, Z_NEAR, Z_FAR);
+        } else if anti_aliasing == AntiAliasing::Fxaa {
+            fxaa(&mut framebuffer);
+        }
+
+        // En las vistas con aristas se indica el nivel de detalle de cada cuerpo
+        if render_mode.draws_edges() {
+            let labeled_bodies = [
+                (&lod_sun, &sphere_sun),
+                (&lod_mercury, &sphere_mercury),
+                (&lod_venus, &sphere_venus),
+                (&lod_earth, &sphere_earth),
+                (&lod_moon, &sphere_moon),
+                (&lod_mars, &sphere_mars),
+                (&lod_jupiter, &sphere_jupiter),
+                (&lod_saturn, &sphere_saturn),
+                (&lod_uranus, &sphere_uranus),
+                (&lod_neptune, &sphere_neptune),
+                (&lod_station, &sphere_station),
+            ];
+            for (mesh, sphere) in labeled_bodies {
+                draw_lod_label(&mut framebuffer, &uniforms_world, mesh, sphere);
+            }
+        }
+
+        // Panel de información sobre la imagen final, sin tone mapping ni post-procesado
+        if let Some((body, sphere, sun_distance_au, orbital_speed_km_s)) = selected_body.map(|index| selectable_bodies[index]) {
+            if let Some((center, radius)) = screen_circle(&sphere, &uniforms_world) {
+                let ssaa = framebuffer.ssaa as f32;
+                draw_selection_marker(&mut framebuffer, (center.x / ssaa, center.y / ssaa), (radius / ssaa).max(PICK_RADIUS_PIXELS) + 3.0);
+            }
+
+            // La distancia a la cámara se mide a la escala del propio cuerpo: a esa distancia
+            // en km el cuerpo real se vería del mismo tamaño que en pantalla
+            let camera_distance_km = (camera.eye - sphere.center).magnitude() * body.radius_km / sphere.radius;
+            let satellites = catalog.children(&body.name).map(|child| child.name.as_str()).collect();
+            draw_info_panel(&mut framebuffer, &BodyInfo { body, satellites, sun_distance_au, camera_distance_km, orbital_speed_km_s });
+        }
 
         window
             .update_with_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height)
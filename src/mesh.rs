@@ -0,0 +1,232 @@
+use nalgebra_glm::{Vec2, Vec3, Vec4};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use crate::culling::BoundingSphere;
+use crate::vertex::Vertex;
+
+// Ángulo máximo entre caras para promediar sus normales cuando un modelo no trae normales
+pub const DEFAULT_SMOOTHING_ANGLE: f32 = PI / 3.0;
+
+// Malla indexada: cada vértice distinto se guarda una sola vez y cada triángulo es una
+// terna de índices sobre `vertices`, en orden antihorario visto desde afuera. Así el vertex
+// shader corre una vez por vértice y no una vez por esquina de triángulo.
+#[derive(Debug, Clone, Default)]
+pub struct Mesh {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+    // Tangente por vértice (xyz) y signo de la bitangente (w) para mapas de normales. Vacío
+    // hasta llamar a `compute_tangents`; las operaciones que rehacen los vértices lo vacían.
+    pub tangents: Vec<Vec4>,
+}
+
+impl Mesh {
+    pub fn new(vertices: Vec<Vertex>, indices: Vec<u32>) -> Self {
+        Mesh { vertices, indices, tangents: Vec::new() }
+    }
+
+    // A partir de una lista expandida (tres vértices por triángulo). Las esquinas con
+    // exactamente los mismos atributos pasan a compartir un vértice.
+    pub fn from_triangles(corners: &[Vertex]) -> Self {
+        let mut mesh = Mesh::default();
+        let mut lookup: HashMap<[u32; 9], u32> = HashMap::new();
+
+        for corner in &corners[..corners.len() / 3 * 3] {
+            let index = *lookup.entry(vertex_key(corner)).or_insert_with(|| {
+                mesh.vertices.push(corner.clone());
+                (mesh.vertices.len() - 1) as u32
+            });
+            mesh.indices.push(index);
+        }
+        mesh
+    }
+
+    // Índices de cada triángulo
+    pub fn triangles(&self) -> impl Iterator<Item = [usize; 3]> + '_ {
+        self.indices.chunks_exact(3).map(|tri| [tri[0] as usize, tri[1] as usize, tri[2] as usize])
+    }
+
+    pub fn positions(&self) -> Vec<Vec3> {
+        self.vertices.iter().map(|vertex| vertex.position).collect()
+    }
+
+    // Agrega los triángulos de `other` desplazando sus índices
+    pub fn append(&mut self, other: &Mesh) {
+        let offset = self.vertices.len() as u32;
+        self.vertices.extend(other.vertices.iter().cloned());
+        self.indices.extend(other.indices.iter().map(|index| index + offset));
+        self.tangents.clear();
+    }
+
+    // Normales calculadas desde la geometría. En cada esquina se suman, pesadas por área,
+    // las normales de las caras que comparten la posición y forman con la cara de la esquina
+    // un ángulo de hasta `max_angle` (radianes): con 0 quedan normales planas, con π la malla
+    // queda suave del todo y con valores intermedios se conservan las aristas marcadas.
+    pub fn compute_normals(&mut self, max_angle: f32) {
+        let face_normals: Vec<Vec3> = self
+            .triangles()
+            .map(|[a, b, c]| {
+                let (a, b, c) = (self.vertices[a].position, self.vertices[b].position, self.vertices[c].position);
+                (b - a).cross(&(c - a))
+            })
+            .collect();
+
+        let mut faces_at: HashMap<[u32; 3], Vec<usize>> = HashMap::new();
+        for (face, tri) in self.triangles().enumerate() {
+            for index in tri {
+                faces_at.entry(position_key(&self.vertices[index].position)).or_default().push(face);
+            }
+        }
+
+        let min_cos = max_angle.cos() - 1e-5;
+        let mut corners = Vec::with_capacity(self.indices.len());
+        for (face, tri) in self.triangles().enumerate() {
+            let direction = face_normals[face].try_normalize(f32::EPSILON);
+            for index in tri {
+                let vertex = &self.vertices[index];
+                let sum = faces_at[&position_key(&vertex.position)]
+                    .iter()
+                    .filter(|&&other| match (direction, face_normals[other].try_normalize(f32::EPSILON)) {
+                        (Some(direction), Some(other)) => direction.dot(&other) >= min_cos,
+                        _ => other == face,
+                    })
+                    .fold(Vec3::zeros(), |sum, &other| sum + face_normals[other]);
+
+                let mut corner = vertex.clone();
+                corner.normal = sum.try_normalize(f32::EPSILON).or(direction).unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+                corners.push(corner);
+            }
+        }
+
+        *self = Mesh::from_triangles(&corners);
+    }
+
+    // Une los vértices que quedaron duplicados: posiciones a menos de `epsilon` y con la
+    // misma normal, UV y color. Los triángulos que se degeneran al unirse se descartan.
+    // Las posiciones se comparan sobre una rejilla de lado `epsilon`, así que dos vértices
+    // muy cercanos pero a ambos lados de una celda no se unen. Sin una distancia positiva no
+    // hay rejilla y la malla queda como está.
+    pub fn weld(&mut self, epsilon: f32) {
+        if epsilon.is_nan() || epsilon <= 0.0 {
+            return;
+        }
+        let quantize = |value: f32, step: f32| (value / step).round() as i32;
+        let key = |vertex: &Vertex| {
+            let (p, n, t) = (vertex.position, vertex.normal, vertex.tex_coords);
+            [
+                quantize(p.x, epsilon), quantize(p.y, epsilon), quantize(p.z, epsilon),
+                quantize(n.x, 1e-3), quantize(n.y, 1e-3), quantize(n.z, 1e-3),
+                quantize(t.x, 1e-4), quantize(t.y, 1e-4),
+                vertex.color.to_hex() as i32,
+            ]
+        };
+
+        let mut lookup: HashMap<[i32; 9], u32> = HashMap::new();
+        let mut vertices = Vec::new();
+        let remap: Vec<u32> = self
+            .vertices
+            .iter()
+            .map(|vertex| {
+                *lookup.entry(key(vertex)).or_insert_with(|| {
+                    vertices.push(vertex.clone());
+                    (vertices.len() - 1) as u32
+                })
+            })
+            .collect();
+
+        let indices = self
+            .indices
+            .chunks_exact(3)
+            .map(|tri| [remap[tri[0] as usize], remap[tri[1] as usize], remap[tri[2] as usize]])
+            .filter(|[a, b, c]| a != b && b != c && c != a)
+            .flatten()
+            .collect();
+
+        *self = Mesh::new(vertices, indices);
+    }
+
+    // Tangentes a partir de las UV (método de Lengyel), ortogonalizadas contra la normal
+    pub fn compute_tangents(&mut self) {
+        let mut tangents = vec![Vec3::zeros(); self.vertices.len()];
+        let mut bitangents = vec![Vec3::zeros(); self.vertices.len()];
+
+        for tri in self.triangles() {
+            let [a, b, c] = tri.map(|index| &self.vertices[index]);
+            let (edge1, edge2) = (b.position - a.position, c.position - a.position);
+            let (duv1, duv2): (Vec2, Vec2) = (b.tex_coords - a.tex_coords, c.tex_coords - a.tex_coords);
+
+            let determinant = duv1.x * duv2.y - duv2.x * duv1.y;
+            if determinant.abs() <= f32::EPSILON {
+                continue;
+            }
+            let r = 1.0 / determinant;
+            let tangent = (edge1 * duv2.y - edge2 * duv1.y) * r;
+            let bitangent = (edge2 * duv1.x - edge1 * duv2.x) * r;
+
+            for index in tri {
+                tangents[index] += tangent;
+                bitangents[index] += bitangent;
+            }
+        }
+
+        self.tangents = self
+            .vertices
+            .iter()
+            .zip(tangents.iter().zip(&bitangents))
+            .map(|(vertex, (tangent, bitangent))| {
+                let normal = vertex.normal;
+                // Sin UV útiles se elige cualquier dirección perpendicular a la normal
+                let fallback = if normal.x.abs() < 0.9 { Vec3::new(1.0, 0.0, 0.0) } else { Vec3::new(0.0, 1.0, 0.0) };
+                let tangent = (tangent - normal * normal.dot(tangent))
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or_else(|| (fallback - normal * normal.dot(&fallback)).normalize());
+                let handedness = if normal.cross(&tangent).dot(bitangent) < 0.0 { -1.0 } else { 1.0 };
+                Vec4::new(tangent.x, tangent.y, tangent.z, handedness)
+            })
+            .collect();
+    }
+
+    // Tangente media de un triángulo, `None` si la malla no tiene tangentes
+    pub fn face_tangent(&self, [a, b, c]: [usize; 3]) -> Option<Vec3> {
+        let (ta, tb, tc) = (self.tangents.get(a)?, self.tangents.get(b)?, self.tangents.get(c)?);
+        (ta.xyz() + tb.xyz() + tc.xyz()).try_normalize(f32::EPSILON)
+    }
+
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        BoundingSphere::from_points(&self.positions())
+    }
+
+    // Mueve la malla para que su esfera envolvente quede centrada en el origen
+    pub fn recenter(&mut self) {
+        let center = self.bounding_sphere().center;
+        for vertex in self.vertices.iter_mut() {
+            vertex.position -= center;
+        }
+    }
+
+    // Centra la malla y la escala para que su esfera envolvente tenga radio 1
+    pub fn normalize(&mut self) {
+        self.recenter();
+        let radius = self.bounding_sphere().radius;
+        if radius <= 0.0 {
+            return;
+        }
+        for vertex in self.vertices.iter_mut() {
+            vertex.position /= radius;
+        }
+    }
+}
+
+fn position_key(position: &Vec3) -> [u32; 3] {
+    [position.x.to_bits(), position.y.to_bits(), position.z.to_bits()]
+}
+
+// Atributos de entrada del vértice como bits, para comparar vértices exactamente iguales
+fn vertex_key(vertex: &Vertex) -> [u32; 9] {
+    let (p, n, t) = (vertex.position, vertex.normal, vertex.tex_coords);
+    [
+        p.x.to_bits(), p.y.to_bits(), p.z.to_bits(),
+        n.x.to_bits(), n.y.to_bits(), n.z.to_bits(),
+        t.x.to_bits(), t.y.to_bits(),
+        vertex.color.to_hex(),
+    ]
+}
@@ -0,0 +1,96 @@
+use nalgebra_glm::Vec3;
+
+// Hash entero (variante de lowbias32) usado como base para ruido y números aleatorios
+pub fn hash(mut x: u32) -> u32 {
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7FEB_352D);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846C_A68B);
+    x ^= x >> 16;
+    x
+}
+
+fn hash_lattice(x: i32, y: i32, z: i32, seed: u32) -> f32 {
+    let h = hash(
+        (x as u32).wrapping_mul(0x8DA6_B343)
+            ^ (y as u32).wrapping_mul(0xD816_3841)
+            ^ (z as u32).wrapping_mul(0xCB1A_B31F)
+            ^ seed,
+    );
+    h as f32 / u32::MAX as f32
+}
+
+fn smooth(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+// Transición suave de 0 a 1 entre `edge0` y `edge1`, como la de GLSL
+pub fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    smooth(((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0))
+}
+
+// Value noise 3D en [0, 1]
+pub fn value_noise(p: &Vec3, seed: u32) -> f32 {
+    let (ix, iy, iz) = (p.x.floor() as i32, p.y.floor() as i32, p.z.floor() as i32);
+    let (fx, fy, fz) = (smooth(p.x - ix as f32), smooth(p.y - iy as f32), smooth(p.z - iz as f32));
+
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+    let corner = |dx: i32, dy: i32, dz: i32| hash_lattice(ix + dx, iy + dy, iz + dz, seed);
+
+    let x00 = lerp(corner(0, 0, 0), corner(1, 0, 0), fx);
+    let x10 = lerp(corner(0, 1, 0), corner(1, 1, 0), fx);
+    let x01 = lerp(corner(0, 0, 1), corner(1, 0, 1), fx);
+    let x11 = lerp(corner(0, 1, 1), corner(1, 1, 1), fx);
+
+    lerp(lerp(x00, x10, fy), lerp(x01, x11, fy), fz)
+}
+
+// Suma de octavas de value noise, normalizada a [0, 1]
+pub fn fbm(p: &Vec3, octaves: u32, seed: u32) -> f32 {
+    let mut value = 0.0;
+    let mut amplitude = 0.5;
+    let mut total = 0.0;
+    let mut point = *p;
+
+    for octave in 0..octaves {
+        value += value_noise(&point, seed.wrapping_add(octave)) * amplitude;
+        total += amplitude;
+        amplitude *= 0.5;
+        point *= 2.0;
+    }
+
+    value / total
+}
+
+// Generador pseudoaleatorio determinista para contenido procedural
+pub struct Random {
+    state: u32,
+}
+
+impl Random {
+    pub fn new(seed: u32) -> Self {
+        Random { state: hash(seed ^ 0x9E37_79B9) }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        self.state = self.state.wrapping_add(0x9E37_79B9);
+        hash(self.state)
+    }
+
+    // Uniforme en [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1u32 << 24) as f32
+    }
+
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    // Dirección uniforme sobre la esfera unitaria
+    pub fn unit_vector(&mut self) -> Vec3 {
+        let z = self.range(-1.0, 1.0);
+        let phi = self.range(0.0, std::f32::consts::TAU);
+        let r = (1.0 - z * z).sqrt();
+        Vec3::new(r * phi.cos(), z, r * phi.sin())
+    }
+}
@@ -1,16 +1,11 @@
-use tobj;
 use nalgebra_glm::{Vec2, Vec3};
 use crate::vertex::Vertex;
+use crate::mesh::{Mesh, DEFAULT_SMOOTHING_ANGLE};
+use crate::culling::BoundingSphere;
 
 pub struct Obj {
-    meshes: Vec<Mesh>,
-}
-
-struct Mesh {
-    vertices: Vec<Vec3>,
-    normals: Vec<Vec3>,
-    texcoords: Vec<Vec2>,
-    indices: Vec<u32>,
+    mesh: Mesh,
+    bounding_sphere: BoundingSphere,
 }
 
 impl Obj {
@@ -21,42 +16,56 @@ impl Obj {
             ..Default::default()
         })?;
 
-        let meshes = models.into_iter().map(|model| {
-            let mesh = model.mesh;
-            Mesh {
-                vertices: mesh.positions.chunks(3)
-                    .map(|v| Vec3::new(v[0], v[1], v[2]))
-                    .collect(),
-                normals: mesh.normals.chunks(3)
-                    .map(|n| Vec3::new(n[0], n[1], n[2]))
-                    .collect(),
-                texcoords: mesh.texcoords.chunks(2)
-                    .map(|t| Vec2::new(t[0], 1.0 - t[1]))
-                    .collect(),
-                indices: mesh.indices,
+        // Con `single_index` tobj deja un vértice por combinación distinta de posición,
+        // normal y UV, así que los índices ya comparten vértices
+        let mut mesh = Mesh::default();
+        for model in models {
+            let data = model.mesh;
+            let vertices = data.positions.chunks(3)
+                .enumerate()
+                .map(|(index, v)| {
+                    let normal = data.normals.get(index * 3..index * 3 + 3)
+                        .map(|n| Vec3::new(n[0], n[1], n[2]))
+                        .unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+                    let tex_coords = data.texcoords.get(index * 2..index * 2 + 2)
+                        .map(|t| Vec2::new(t[0], 1.0 - t[1]))
+                        .unwrap_or(Vec2::new(0.0, 0.0));
+                    Vertex::new(Vec3::new(v[0], v[1], v[2]), normal, tex_coords)
+                })
+                .collect();
+
+            let mut part = Mesh::new(vertices, data.indices);
+            // Sin normales en el archivo se calculan desde la geometría
+            if data.normals.is_empty() {
+                part.compute_normals(DEFAULT_SMOOTHING_ANGLE);
             }
-        }).collect();
+            mesh.append(&part);
+        }
 
-        Ok(Obj { meshes })
+        let bounding_sphere = mesh.bounding_sphere();
+        Ok(Obj { mesh, bounding_sphere })
     }
 
-    pub fn get_vertex_array(&self) -> Vec<Vertex> {
-        let mut vertices = Vec::new();
+    // Centra el modelo en el origen y lo escala a radio envolvente 1
+    pub fn normalize(&mut self) {
+        self.mesh.normalize();
+        self.bounding_sphere = self.mesh.bounding_sphere();
+    }
 
-        for mesh in &self.meshes {
-            for &index in &mesh.indices {
-                let position = mesh.vertices[index as usize];
-                let normal = mesh.normals.get(index as usize)
-                    .cloned()
-                    .unwrap_or(Vec3::new(0.0, 1.0, 0.0));
-                let tex_coords = mesh.texcoords.get(index as usize)
-                    .cloned()
-                    .unwrap_or(Vec2::new(0.0, 0.0));
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        self.bounding_sphere
+    }
 
-                vertices.push(Vertex::new(position, normal, tex_coords));
-            }
-        }
+    // Distancia media de los vértices al centro de la esfera envolvente. Para mallas casi
+    // esféricas es una mejor estimación de la superficie que el radio envolvente.
+    pub fn mean_radius(&self) -> f32 {
+        let center = self.bounding_sphere.center;
+        let (sum, count) = self.mesh.vertices.iter()
+            .fold((0.0, 0), |(sum, count), vertex| (sum + (vertex.position - center).magnitude(), count + 1));
+        if count == 0 { 0.0 } else { sum / count as f32 }
+    }
 
-        vertices
+    pub fn mesh(&self) -> Mesh {
+        self.mesh.clone()
     }
 }
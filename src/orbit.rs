@@ -0,0 +1,133 @@
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+use crate::ephemeris::PlanetEphemeris;
+
+// Constante de gravitación en km³/(kg·s²)
+const GRAVITATIONAL_CONSTANT_KM: f64 = 6.6743e-20;
+
+// Órbita kepleriana alrededor de `center`. El plano de referencia es XZ (y hacia arriba).
+#[derive(Debug, Clone, Copy)]
+pub struct Orbit {
+    pub center: Vec3,
+    pub semi_major_axis: f32,
+    pub eccentricity: f32,
+    pub inclination: f32,
+    pub longitude_of_node: f32,
+    pub argument_of_periapsis: f32,
+    pub period: f32, // en frames
+    pub phase: f32,  // anomalía media en time = 0
+}
+
+impl Orbit {
+    pub fn new(center: Vec3, semi_major_axis: f32, eccentricity: f32, period: f32) -> Self {
+        Orbit {
+            center,
+            semi_major_axis,
+            eccentricity,
+            inclination: 0.0,
+            longitude_of_node: 0.0,
+            argument_of_periapsis: 0.0,
+            period,
+            phase: 0.0,
+        }
+    }
+
+    // Órbita real en UA con los elementos de `ephemeris` en el día juliano `julian_date`, que
+    // queda en time = 0. `days_per_frame` es el paso del reloj de la simulación. La forma y
+    // la orientación quedan fijas; la anomalía media avanza al ritmo de la longitud media
+    // de la tabla, así la posición no se desvía de las efemérides en las corridas largas.
+    pub fn from_ephemeris(center: Vec3, ephemeris: &PlanetEphemeris, julian_date: f64, days_per_frame: f32) -> Self {
+        let elements = ephemeris.elements_at(julian_date);
+        let period = (ephemeris.period_days() / days_per_frame as f64) as f32;
+        Orbit::new(center, elements.semi_major_axis as f32, elements.eccentricity as f32, period)
+            .with_orientation(
+                (elements.inclination as f32).to_radians(),
+                (elements.longitude_of_node as f32).to_radians(),
+                (elements.argument_of_perihelion() as f32).to_radians(),
+            )
+            .with_phase((elements.mean_anomaly() as f32).to_radians())
+    }
+
+    pub fn with_orientation(mut self, inclination: f32, longitude_of_node: f32, argument_of_periapsis: f32) -> Self {
+        self.inclination = inclination;
+        self.longitude_of_node = longitude_of_node;
+        self.argument_of_periapsis = argument_of_periapsis;
+        self
+    }
+
+    pub fn with_phase(mut self, phase: f32) -> Self {
+        self.phase = phase;
+        self
+    }
+
+    pub fn mean_anomaly(&self, time: f32) -> f32 {
+        (self.phase + 2.0 * PI * time / self.period) % (2.0 * PI)
+    }
+
+    pub fn position_at(&self, time: f32) -> Vec3 {
+        let eccentric_anomaly = solve_kepler(self.mean_anomaly(time), self.eccentricity);
+        self.position_at_eccentric_anomaly(eccentric_anomaly)
+    }
+
+    // Rapidez en km/s por la ecuación vis-viva, v² = GM·(2/r − 1/a). `mass_kg` es la masa
+    // total del sistema y `km_per_unit` convierte las unidades de la órbita a km.
+    pub fn speed_at(&self, time: f32, mass_kg: f64, km_per_unit: f64) -> f32 {
+        let distance = (self.position_at(time) - self.center).magnitude() as f64 * km_per_unit;
+        let semi_major_axis = self.semi_major_axis as f64 * km_per_unit;
+        if distance <= 0.0 {
+            return 0.0;
+        }
+        (GRAVITATIONAL_CONSTANT_KM * mass_kg * (2.0 / distance - 1.0 / semi_major_axis)).max(0.0).sqrt() as f32
+    }
+
+    pub fn position_at_eccentric_anomaly(&self, eccentric_anomaly: f32) -> Vec3 {
+        let a = self.semi_major_axis;
+        let e = self.eccentricity;
+
+        // Coordenadas en el plano de la órbita, con el foco en el origen
+        let x = a * (eccentric_anomaly.cos() - e);
+        let y = a * (1.0 - e * e).sqrt() * eccentric_anomaly.sin();
+
+        self.center + self.orient(x, y)
+    }
+
+    // Puntos de la elipse completa; el último repite el primero para cerrar el trazo
+    pub fn points(&self, segments: usize) -> Vec<Vec3> {
+        (0..=segments)
+            .map(|i| self.position_at_eccentric_anomaly(2.0 * PI * i as f32 / segments as f32))
+            .collect()
+    }
+
+    fn orient(&self, x: f32, y: f32) -> Vec3 {
+        let (sin_w, cos_w) = self.argument_of_periapsis.sin_cos();
+        let (sin_o, cos_o) = self.longitude_of_node.sin_cos();
+        let (sin_i, cos_i) = self.inclination.sin_cos();
+
+        // Rotaciones clásicas Rz(Ω) Rx(i) Rz(ω) en un sistema con z como normal de la eclíptica
+        let xp = x * cos_w - y * sin_w;
+        let yp = x * sin_w + y * cos_w;
+
+        let ex = xp * cos_o - yp * cos_i * sin_o;
+        let ey = xp * sin_o + yp * cos_i * cos_o;
+        let ez = yp * sin_i;
+
+        // La eclíptica (x, y) se mapea al plano XZ de la escena
+        Vec3::new(ex, ez, -ey)
+    }
+}
+
+// Resuelve M = E - e sin(E) por Newton-Raphson
+pub fn solve_kepler(mean_anomaly: f32, eccentricity: f32) -> f32 {
+    let mut eccentric_anomaly = if eccentricity > 0.8 { PI } else { mean_anomaly };
+
+    for _ in 0..10 {
+        let delta = (eccentric_anomaly - eccentricity * eccentric_anomaly.sin() - mean_anomaly)
+            / (1.0 - eccentricity * eccentric_anomaly.cos());
+        eccentric_anomaly -= delta;
+        if delta.abs() < 1e-6 {
+            break;
+        }
+    }
+
+    eccentric_anomaly
+}
@@ -0,0 +1,321 @@
+use nalgebra_glm::Vec3;
+use crate::color::LinearColor;
+use crate::framebuffer::Framebuffer;
+
+// Operadores para llevar el color HDR lineal al rango [0, 1] antes de codificar en sRGB
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToneMapping {
+    None,
+    Reinhard,
+    AcesFilmic,
+    Exposure,
+}
+
+impl ToneMapping {
+    pub fn name(self) -> &'static str {
+        match self {
+            ToneMapping::None => "Sin tone mapping",
+            ToneMapping::Reinhard => "Reinhard",
+            ToneMapping::AcesFilmic => "ACES filmic",
+            ToneMapping::Exposure => "Exposure",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            ToneMapping::None => ToneMapping::Reinhard,
+            ToneMapping::Reinhard => ToneMapping::AcesFilmic,
+            ToneMapping::AcesFilmic => ToneMapping::Exposure,
+            ToneMapping::Exposure => ToneMapping::None,
+        }
+    }
+
+    pub fn apply(self, color: LinearColor) -> LinearColor {
+        match self {
+            ToneMapping::None => color,
+            ToneMapping::Reinhard => {
+                LinearColor::new(color.r / (1.0 + color.r), color.g / (1.0 + color.g), color.b / (1.0 + color.b))
+            }
+            ToneMapping::AcesFilmic => {
+                // Ajuste de curva de Narkowicz para el RRT + ODT de ACES
+                let aces = |x: f32| {
+                    let x = x.max(0.0);
+                    ((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)).clamp(0.0, 1.0)
+                };
+                LinearColor::new(aces(color.r), aces(color.g), aces(color.b))
+            }
+            ToneMapping::Exposure => {
+                LinearColor::new(1.0 - (-color.r).exp(), 1.0 - (-color.g).exp(), 1.0 - (-color.b).exp())
+            }
+        }
+    }
+}
+
+// Resplandor alrededor de las zonas brillantes (el Sol). Trabaja sobre el buffer HDR:
+// se extraen los píxeles cuya luminancia supera `threshold`, se desenfocan a resolución
+// reducida con un gaussiano separable de radio `radius` y se suman de vuelta a la imagen.
+pub struct Bloom {
+    pub threshold: f32,
+    pub radius: usize,
+    pub intensity: f32,
+    pub downsample: usize,
+}
+
+impl Bloom {
+    pub fn new(threshold: f32, radius: usize) -> Self {
+        Bloom {
+            threshold,
+            radius,
+            intensity: 1.0,
+            downsample: 4,
+        }
+    }
+
+    pub fn apply(&self, framebuffer: &mut Framebuffer) {
+        // Con SSAA el radio se mide en píxeles de salida, no de render
+        let factor = self.downsample.max(1) * framebuffer.ssaa;
+        let width = framebuffer.width.div_ceil(factor);
+        let height = framebuffer.height.div_ceil(factor);
+
+        // Extracción de brillo y reducción de resolución
+        let mut bright = vec![Vec3::zeros(); width * height];
+        for y in 0..framebuffer.height {
+            for x in 0..framebuffer.width {
+                let texel = framebuffer.color_buffer[y * framebuffer.width + x];
+                let lum = texel.luminance();
+                if lum > self.threshold {
+                    let color = to_rgb(&texel);
+                    bright[(y / factor) * width + x / factor] += color * ((lum - self.threshold) / lum);
+                }
+            }
+        }
+        let area = (factor * factor) as f32;
+        for texel in bright.iter_mut() {
+            *texel /= area;
+        }
+
+        // Desenfoque gaussiano separable
+        let kernel = gaussian_kernel(self.radius);
+        let mut temp = vec![Vec3::zeros(); width * height];
+        blur(&bright, &mut temp, width, height, &kernel, (1, 0));
+        blur(&temp, &mut bright, width, height, &kernel, (0, 1));
+
+        // Composición aditiva con muestreo bilineal del buffer reducido
+        for y in 0..framebuffer.height {
+            for x in 0..framebuffer.width {
+                let u = (x as f32 + 0.5) / factor as f32 - 0.5;
+                let v = (y as f32 + 0.5) / factor as f32 - 0.5;
+                let glow = sample_bilinear(&bright, width, height, u, v) * self.intensity;
+                if glow.max() <= 0.0 {
+                    continue;
+                }
+
+                framebuffer.color_buffer[y * framebuffer.width + x] += LinearColor::new(glow.x, glow.y, glow.z);
+            }
+        }
+    }
+}
+
+fn to_rgb(color: &LinearColor) -> Vec3 {
+    Vec3::new(color.r, color.g, color.b)
+}
+
+// Pesos normalizados para offsets -radius..=radius
+pub fn gaussian_kernel(radius: usize) -> Vec<f32> {
+    let sigma = (radius as f32 / 2.0).max(0.5);
+    let weights: Vec<f32> = (-(radius as i32)..=radius as i32)
+        .map(|i| (-(i * i) as f32 / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total: f32 = weights.iter().sum();
+    weights.into_iter().map(|w| w / total).collect()
+}
+
+// Una pasada del gaussiano en la dirección (dx, dy), con los bordes extendidos
+pub fn blur(source: &[Vec3], target: &mut [Vec3], width: usize, height: usize, kernel: &[f32], direction: (i32, i32)) {
+    let radius = (kernel.len() / 2) as i32;
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut sum = Vec3::zeros();
+            for (k, weight) in kernel.iter().enumerate() {
+                let offset = k as i32 - radius;
+                let sx = (x as i32 + offset * direction.0).clamp(0, width as i32 - 1) as usize;
+                let sy = (y as i32 + offset * direction.1).clamp(0, height as i32 - 1) as usize;
+                sum += source[sy * width + sx] * *weight;
+            }
+            target[y * width + x] = sum;
+        }
+    }
+}
+
+fn sample_bilinear(source: &[Vec3], width: usize, height: usize, u: f32, v: f32) -> Vec3 {
+    let u = u.clamp(0.0, width as f32 - 1.0);
+    let v = v.clamp(0.0, height as f32 - 1.0);
+    let (x0, y0) = (u.floor() as usize, v.floor() as usize);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fx, fy) = (u - x0 as f32, v - y0 as f32);
+
+    let top = source[y0 * width + x0] * (1.0 - fx) + source[y0 * width + x1] * fx;
+    let bottom = source[y1 * width + x0] * (1.0 - fx) + source[y1 * width + x1] * fx;
+    top * (1.0 - fy) + bottom * fy
+}
+
+const FXAA_EDGE_THRESHOLD_MIN: f32 = 0.0312;
+const FXAA_EDGE_THRESHOLD_MAX: f32 = 0.125;
+const FXAA_SUBPIXEL_QUALITY: f32 = 0.75;
+const FXAA_SEARCH_STEPS: usize = 12;
+const FXAA_STEP_SIZES: [f32; FXAA_SEARCH_STEPS] = [1.0, 1.0, 1.0, 1.0, 1.0, 1.5, 2.0, 2.0, 2.0, 2.0, 4.0, 8.0];
+
+// Antialiasing en espacio de pantalla al estilo FXAA 3.11. Trabaja sobre el buffer
+// presentable ya codificado en sRGB: detecta bordes por contraste de luminancia,
+// recorre el borde en ambas direcciones para estimar su extremo y mezcla cada píxel con
+// su vecino a través del borde en proporción a su distancia al extremo.
+pub fn fxaa(framebuffer: &mut Framebuffer) {
+    let width = framebuffer.output_width;
+    let height = framebuffer.output_height;
+
+    let image: Vec<Vec3> = framebuffer.buffer.iter().map(|&hex| unpack(hex)).collect();
+    // La luma se calcula con los pesos de luminancia sobre los valores ya codificados
+    let luma: Vec<f32> = image.iter().map(|color| LinearColor::new(color.x, color.y, color.z).luminance()).collect();
+
+    let luma_at = |x: i32, y: i32| luma[(y.clamp(0, height as i32 - 1) as usize) * width + x.clamp(0, width as i32 - 1) as usize];
+    let luma_bilinear = |x: f32, y: f32| {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i32, y0 as i32);
+        let top = luma_at(x0, y0) * (1.0 - fx) + luma_at(x0 + 1, y0) * fx;
+        let bottom = luma_at(x0, y0 + 1) * (1.0 - fx) + luma_at(x0 + 1, y0 + 1) * fx;
+        top * (1.0 - fy) + bottom * fy
+    };
+
+    for y in 0..height as i32 {
+        for x in 0..width as i32 {
+            let luma_center = luma_at(x, y);
+            let luma_down = luma_at(x, y + 1);
+            let luma_up = luma_at(x, y - 1);
+            let luma_left = luma_at(x - 1, y);
+            let luma_right = luma_at(x + 1, y);
+
+            let luma_min = luma_center.min(luma_down).min(luma_up).min(luma_left).min(luma_right);
+            let luma_max = luma_center.max(luma_down).max(luma_up).max(luma_left).max(luma_right);
+            let luma_range = luma_max - luma_min;
+
+            // Zonas sin contraste suficiente no se tocan
+            if luma_range < FXAA_EDGE_THRESHOLD_MIN.max(luma_max * FXAA_EDGE_THRESHOLD_MAX) {
+                continue;
+            }
+
+            let luma_down_left = luma_at(x - 1, y + 1);
+            let luma_up_right = luma_at(x + 1, y - 1);
+            let luma_up_left = luma_at(x - 1, y - 1);
+            let luma_down_right = luma_at(x + 1, y + 1);
+
+            let luma_down_up = luma_down + luma_up;
+            let luma_left_right = luma_left + luma_right;
+            let luma_left_corners = luma_down_left + luma_up_left;
+            let luma_down_corners = luma_down_left + luma_down_right;
+            let luma_right_corners = luma_down_right + luma_up_right;
+            let luma_up_corners = luma_up_right + luma_up_left;
+
+            let edge_horizontal = (-2.0 * luma_left + luma_left_corners).abs()
+                + (-2.0 * luma_center + luma_down_up).abs() * 2.0
+                + (-2.0 * luma_right + luma_right_corners).abs();
+            let edge_vertical = (-2.0 * luma_up + luma_up_corners).abs()
+                + (-2.0 * luma_center + luma_left_right).abs() * 2.0
+                + (-2.0 * luma_down + luma_down_corners).abs();
+            let is_horizontal = edge_horizontal >= edge_vertical;
+
+            // Lado del borde con mayor gradiente
+            let (luma1, luma2) = if is_horizontal { (luma_up, luma_down) } else { (luma_left, luma_right) };
+            let gradient1 = luma1 - luma_center;
+            let gradient2 = luma2 - luma_center;
+            let is_1_steepest = gradient1.abs() >= gradient2.abs();
+            let gradient_scaled = 0.25 * gradient1.abs().max(gradient2.abs());
+
+            let mut step_length = if is_1_steepest { -1.0 } else { 1.0 };
+            let luma_local_average = if is_1_steepest {
+                0.5 * (luma1 + luma_center)
+            } else {
+                0.5 * (luma2 + luma_center)
+            };
+
+            // Punto medio entre el píxel y su vecino a través del borde
+            let (mut u, mut v) = (x as f32, y as f32);
+            if is_horizontal {
+                v += step_length * 0.5;
+            } else {
+                u += step_length * 0.5;
+            }
+            let (offset_u, offset_v) = if is_horizontal { (1.0, 0.0) } else { (0.0, 1.0) };
+
+            // Exploración a lo largo del borde hasta que la luminancia cambia
+            let (mut u1, mut v1) = (u - offset_u * FXAA_STEP_SIZES[0], v - offset_v * FXAA_STEP_SIZES[0]);
+            let (mut u2, mut v2) = (u + offset_u * FXAA_STEP_SIZES[0], v + offset_v * FXAA_STEP_SIZES[0]);
+            let mut luma_end1 = luma_bilinear(u1, v1) - luma_local_average;
+            let mut luma_end2 = luma_bilinear(u2, v2) - luma_local_average;
+            let mut reached1 = luma_end1.abs() >= gradient_scaled;
+            let mut reached2 = luma_end2.abs() >= gradient_scaled;
+
+            for step in FXAA_STEP_SIZES.iter().skip(1) {
+                if reached1 && reached2 {
+                    break;
+                }
+                if !reached1 {
+                    u1 -= offset_u * step;
+                    v1 -= offset_v * step;
+                    luma_end1 = luma_bilinear(u1, v1) - luma_local_average;
+                    reached1 = luma_end1.abs() >= gradient_scaled;
+                }
+                if !reached2 {
+                    u2 += offset_u * step;
+                    v2 += offset_v * step;
+                    luma_end2 = luma_bilinear(u2, v2) - luma_local_average;
+                    reached2 = luma_end2.abs() >= gradient_scaled;
+                }
+            }
+
+            let distance1 = if is_horizontal { x as f32 - u1 } else { y as f32 - v1 };
+            let distance2 = if is_horizontal { u2 - x as f32 } else { v2 - y as f32 };
+            let is_direction1 = distance1 < distance2;
+            let distance_final = distance1.min(distance2);
+            let edge_thickness = distance1 + distance2;
+
+            // Sólo se desplaza si el extremo más cercano es coherente con la variación local
+            let is_luma_center_smaller = luma_center < luma_local_average;
+            let correct_variation = if is_direction1 {
+                (luma_end1 < 0.0) != is_luma_center_smaller
+            } else {
+                (luma_end2 < 0.0) != is_luma_center_smaller
+            };
+            let pixel_offset = if correct_variation { -distance_final / edge_thickness + 0.5 } else { 0.0 };
+
+            // Suavizado sub-píxel para detalles de un solo píxel
+            let luma_average = (1.0 / 12.0) * (2.0 * (luma_down_up + luma_left_right) + luma_left_corners + luma_right_corners);
+            let sub_pixel_offset1 = ((luma_average - luma_center).abs() / luma_range).clamp(0.0, 1.0);
+            let sub_pixel_offset2 = (-2.0 * sub_pixel_offset1 + 3.0) * sub_pixel_offset1 * sub_pixel_offset1;
+            let sub_pixel_offset = sub_pixel_offset2 * sub_pixel_offset2 * FXAA_SUBPIXEL_QUALITY;
+
+            let final_offset = pixel_offset.max(sub_pixel_offset);
+            if final_offset <= 0.0 {
+                continue;
+            }
+
+            step_length *= final_offset;
+            let (nx, ny) = if is_horizontal { (x, y + step_length.signum() as i32) } else { (x + step_length.signum() as i32, y) };
+            let neighbor = image[(ny.clamp(0, height as i32 - 1) as usize) * width + nx.clamp(0, width as i32 - 1) as usize];
+            let center = image[y as usize * width + x as usize];
+
+            let blended = center + (neighbor - center) * step_length.abs();
+            framebuffer.buffer[y as usize * width + x as usize] = pack(&blended);
+        }
+    }
+}
+
+fn unpack(hex: u32) -> Vec3 {
+    Vec3::new(((hex >> 16) & 0xFF) as f32, ((hex >> 8) & 0xFF) as f32, (hex & 0xFF) as f32) / 255.0
+}
+
+fn pack(color: &Vec3) -> u32 {
+    let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u32;
+    (channel(color.x) << 16) | (channel(color.y) << 8) | channel(color.z)
+}
@@ -0,0 +1,121 @@
+use nalgebra_glm::Vec3;
+use minifb::{Key, KeyRepeat, Window};
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+
+// Modos de visualización para depurar mallas y el z-buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderMode {
+    Shaded,
+    Wireframe,
+    ShadedWireframe,
+    Normals,
+    Depth,
+    TriangleColors,
+    Tangents,
+}
+
+impl RenderMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            RenderMode::Shaded => "Shaded",
+            RenderMode::Wireframe => "Wireframe",
+            RenderMode::ShadedWireframe => "Shaded + Wireframe",
+            RenderMode::Normals => "Normals",
+            RenderMode::Depth => "Depth",
+            RenderMode::TriangleColors => "Triangle colors",
+            RenderMode::Tangents => "Tangents",
+        }
+    }
+
+    // Teclas 1-7 seleccionan el modo directamente
+    pub fn from_input(window: &Window, current: RenderMode) -> RenderMode {
+        let modes = [
+            (Key::Key1, RenderMode::Shaded),
+            (Key::Key2, RenderMode::Wireframe),
+            (Key::Key3, RenderMode::ShadedWireframe),
+            (Key::Key4, RenderMode::Normals),
+            (Key::Key5, RenderMode::Depth),
+            (Key::Key6, RenderMode::TriangleColors),
+            (Key::Key7, RenderMode::Tangents),
+        ];
+
+        modes.iter()
+            .find(|(key, _)| window.is_key_pressed(*key, KeyRepeat::No))
+            .map(|(_, mode)| *mode)
+            .unwrap_or(current)
+    }
+
+    pub fn draws_surfaces(self) -> bool {
+        self != RenderMode::Wireframe
+    }
+
+    // Vistas que reemplazan el sombreado por datos de depuración
+    pub fn is_debug_view(self) -> bool {
+        matches!(self, RenderMode::Normals | RenderMode::Depth | RenderMode::TriangleColors | RenderMode::Tangents)
+    }
+
+    pub fn draws_edges(self) -> bool {
+        matches!(self, RenderMode::Wireframe | RenderMode::ShadedWireframe)
+    }
+}
+
+// Normal en [-1, 1] mapeada a RGB
+pub fn normal_color(normal: &Vec3) -> Color {
+    let n = normal.normalize() * 0.5 + Vec3::new(0.5, 0.5, 0.5);
+    Color::new((n.x * 255.0) as u8, (n.y * 255.0) as u8, (n.z * 255.0) as u8)
+}
+
+// Tangente media de cada triángulo mapeada a RGB como las normales; gris donde la malla no
+// tiene tangentes
+pub fn tangent_color(tangent: Option<Vec3>) -> Color {
+    tangent.map_or(Color::new(128, 128, 128), |tangent| normal_color(&tangent))
+}
+
+// Color pseudoaleatorio estable para cada índice de triángulo
+pub fn triangle_color(index: usize) -> Color {
+    let mut h = (index as u32).wrapping_mul(0x9E37_79B9) ^ 0x85EB_CA6B;
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7FEB_352D);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846C_A68B);
+    h ^= h >> 16;
+
+    // Evita colores demasiado oscuros para que se distingan sobre el fondo
+    Color::new(
+        64 + (h & 0xBF) as u8,
+        64 + ((h >> 8) & 0xBF) as u8,
+        64 + ((h >> 16) & 0xBF) as u8,
+    )
+}
+
+// Reemplaza el color presentable por la profundidad lineal en escala de grises
+// (cerca = claro). El rango se ajusta a las profundidades visibles en el frame.
+pub fn depth_view(framebuffer: &mut Framebuffer, near: f32, far: f32) {
+    let linearize = |z: f32| 2.0 * near * far / (far + near - z * (far - near));
+
+    let depths: Vec<f32> = (0..framebuffer.width * framebuffer.height)
+        .map(|index| framebuffer.pixel_depth(index))
+        .collect();
+
+    let (min, max) = depths.iter()
+        .filter(|z| z.is_finite())
+        .map(|&z| linearize(z))
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), d| (min.min(d), max.max(d)));
+
+    let range = (max - min).max(f32::EPSILON);
+
+    // Con SSAA se toma una muestra por bloque del buffer de render
+    for y in 0..framebuffer.output_height {
+        for x in 0..framebuffer.output_width {
+            let z = depths[y * framebuffer.ssaa * framebuffer.width + x * framebuffer.ssaa];
+            framebuffer.buffer[y * framebuffer.output_width + x] = if z.is_finite() {
+                let value = 1.0 - (linearize(z) - min) / range;
+                let gray = (40.0 + value * 215.0) as u8;
+                Color::new(gray, gray, gray).to_hex()
+            } else {
+                0x000000
+            };
+        }
+    }
+}
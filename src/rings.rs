@@ -0,0 +1,34 @@
+use nalgebra_glm::{Vec2, Vec3};
+use std::f32::consts::PI;
+use crate::mesh::Mesh;
+use crate::vertex::Vertex;
+
+// Bandas de los anillos como (radio interior, radio exterior), en unidades del modelo de
+// los anillos. Entre bandas no hay geometría, así que los huecos dejan ver lo de atrás.
+pub const RING_BANDS: [(f32, f32); 4] = [(1.1, 1.2), (1.3, 1.4), (1.5, 1.6), (1.7, 1.8)];
+
+// Coronas planas en el plano XZ (el ecuador del planeta), centradas en el origen y con la
+// normal hacia +Y. `segments` es el número de divisiones alrededor de cada corona.
+pub fn ring_mesh(segments: usize) -> Mesh {
+    let normal = Vec3::new(0.0, 1.0, 0.0);
+    let mut mesh = Mesh::default();
+
+    for &(inner, outer) in &RING_BANDS {
+        let first = mesh.vertices.len() as u32;
+        for segment in 0..=segments {
+            let u = segment as f32 / segments as f32;
+            let angle = u * 2.0 * PI;
+            for (radius, v) in [(inner, 0.0), (outer, 1.0)] {
+                mesh.vertices.push(Vertex::new(Vec3::new(angle.cos() * radius, 0.0, -angle.sin() * radius), normal, Vec2::new(u, v)));
+            }
+        }
+
+        // Vértices intercalados: interior y exterior de cada división
+        for segment in 0..segments as u32 {
+            let (a, b) = (first + 2 * segment, first + 2 * segment + 1);
+            let (c, d) = (b + 2, a + 2);
+            mesh.indices.extend([a, b, c, a, c, d]);
+        }
+    }
+    mesh
+}
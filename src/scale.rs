@@ -0,0 +1,90 @@
+use nalgebra_glm::Vec3;
+
+pub const KM_PER_AU: f32 = 149_597_870.7;
+pub const EARTH_RADIUS_KM: f32 = 6371.0;
+
+// Unidades de escena por unidad astronómica en los modos con distancias lineales
+const UNITS_PER_AU: f32 = 20.0;
+
+// Compresión logarítmica: d = escala · ln(1 + UA / rodilla). Cerca del Sol es casi lineal
+// y lejos crece lentamente, así Mercurio y Neptuno caben en la misma vista.
+const LOG_DISTANCE_SCALE: f32 = 8.0;
+const LOG_DISTANCE_KNEE: f32 = 0.2;
+
+// Radios exagerados: r = RADIUS_EARTH_UNITS · (km / radio terrestre)^RADIUS_EXPONENT. Con
+// exponente menor que 1 los cuerpos chicos crecen mucho más que el Sol y los gigantes.
+const RADIUS_EARTH_UNITS: f32 = 0.6;
+const RADIUS_EXPONENT: f32 = 0.4;
+
+// Transformación de unidades físicas (UA y km) a unidades de escena. Todas las funciones
+// son monótonas, así que el orden de distancias y de tamaños se conserva en cada modo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleMode {
+    True,        // distancias y radios a la misma escala real
+    Logarithmic, // distancias comprimidas y radios exagerados
+    Exaggerated, // distancias reales con radios exagerados
+}
+
+impl ScaleMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            ScaleMode::True => "Escala real",
+            ScaleMode::Logarithmic => "Distancias logarítmicas",
+            ScaleMode::Exaggerated => "Radios exagerados",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            ScaleMode::True => ScaleMode::Logarithmic,
+            ScaleMode::Logarithmic => ScaleMode::Exaggerated,
+            ScaleMode::Exaggerated => ScaleMode::True,
+        }
+    }
+
+    // Distancia al Sol en UA
+    pub fn distance(self, au: f32) -> f32 {
+        match self {
+            ScaleMode::True | ScaleMode::Exaggerated => au * UNITS_PER_AU,
+            ScaleMode::Logarithmic => LOG_DISTANCE_SCALE * (1.0 + au / LOG_DISTANCE_KNEE).ln(),
+        }
+    }
+
+    // Radio de un cuerpo en km
+    pub fn radius(self, km: f32) -> f32 {
+        match self {
+            ScaleMode::True => km / KM_PER_AU * UNITS_PER_AU,
+            ScaleMode::Logarithmic | ScaleMode::Exaggerated => {
+                RADIUS_EARTH_UNITS * (km / EARTH_RADIUS_KM).powf(RADIUS_EXPONENT)
+            }
+        }
+    }
+
+    // Posición heliocéntrica en UA: se conserva la dirección y se transforma la distancia
+    pub fn position(self, au: Vec3) -> Vec3 {
+        let distance = au.magnitude();
+        if distance <= 0.0 {
+            return Vec3::zeros();
+        }
+        au * (self.distance(distance) / distance)
+    }
+
+    // Desplazamiento de un satélite respecto de su planeta, en km. Con radios exagerados la
+    // distancia se mide en radios del planeta y se comprime para que la órbita quede cerca
+    // del planeta agrandado pero siempre por fuera.
+    pub fn satellite_offset(self, offset_km: Vec3, parent_radius_km: f32) -> Vec3 {
+        let distance = offset_km.magnitude();
+        if distance <= 0.0 {
+            return Vec3::zeros();
+        }
+
+        let scene_distance = match self {
+            ScaleMode::True => distance / KM_PER_AU * UNITS_PER_AU,
+            ScaleMode::Logarithmic | ScaleMode::Exaggerated => {
+                let parent_radii = (distance / parent_radius_km).max(1.0);
+                self.radius(parent_radius_km) * (1.0 + parent_radii.ln())
+            }
+        };
+        offset_km * (scene_distance / distance)
+    }
+}
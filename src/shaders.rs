@@ -1,15 +1,31 @@
-use nalgebra_glm::{dot, mat4_to_mat3, Mat3, Vec2, Vec3, Vec4};
+use nalgebra_glm::{mat4_to_mat3, Mat3, Vec2, Vec3, Vec4};
+use std::sync::OnceLock;
 use crate::vertex::Vertex;
 use crate::Uniforms;
 use crate::fragment::Fragment;
-use crate::color::Color;
-use std::f32::consts::PI;
+use crate::color::{BlendMode, Color, LinearColor};
+use crate::noise::{fbm, smoothstep, value_noise};
+use crate::rings::RING_BANDS;
+use crate::craters::{CraterField, CraterParams};
+use crate::terrain::height_field_normal;
+
+// Luz que conserva un cuerpo dentro de la umbra de otro, para que no quede negro del todo
+const SHADOW_AMBIENT: f32 = 0.06;
+
+// Luz del lado nocturno de los cuerpos iluminados con `sunlit`
+const NIGHT_AMBIENT: f32 = 0.03;
 
 pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
+    // Con relieve la posición y la normal salen del campo de alturas
+    let (displaced_position, displaced_normal) = match &uniforms.terrain {
+        Some(terrain) => terrain.displace(&vertex.position),
+        None => (vertex.position, vertex.normal),
+    };
+
     let position = Vec4::new(
-        vertex.position.x,
-        vertex.position.y,
-        vertex.position.z,
+        displaced_position.x,
+        displaced_position.y,
+        displaced_position.z,
         1.0
     );
 
@@ -28,27 +44,31 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
     let model_mat3 = mat4_to_mat3(&uniforms.model_matrix);
     let normal_matrix = model_mat3.transpose().try_inverse().unwrap_or(Mat3::identity());
 
-    let transformed_normal = normal_matrix * vertex.normal;
+    let transformed_normal = normal_matrix * displaced_normal;
 
     Vertex {
-        position: vertex.position,
-        normal: vertex.normal,
+        position: displaced_position,
+        normal: displaced_normal,
         tex_coords: vertex.tex_coords,
         color: vertex.color,
         transformed_position: Vec3::new(screen_position.x, screen_position.y, screen_position.z),
-        transformed_normal: transformed_normal
+        transformed_normal
     }
 }
 
+#[allow(dead_code)]
 pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     // fragment.color * fragment.intensity
     combined_shader(fragment, uniforms)
-    // combined_blend_shader(fragment, "normal")
-    // combined_blend_shader(fragment, "multiply")
-    // combined_blend_shader(fragment, "add")
-    // combined_blend_shader(fragment, "subtract")
+    // combined_blend_shader(fragment, BlendMode::Normal)
+    // combined_blend_shader(fragment, BlendMode::Multiply)
+    // combined_blend_shader(fragment, BlendMode::Add)
+    // combined_blend_shader(fragment, BlendMode::Subtract)
+    // combined_blend_shader(fragment, BlendMode::Screen)
+    // combined_blend_shader(fragment, BlendMode::Overlay)
 }
 
+#[allow(dead_code)]
 fn static_pattern_shader(fragment: &Fragment) -> Color {
     let x = fragment.vertex_position.x;
     let y = fragment.vertex_position.y;
@@ -62,10 +82,12 @@ fn static_pattern_shader(fragment: &Fragment) -> Color {
     Color::new(r, g, b)
 }
 
+#[allow(dead_code)]
 fn purple_shader(_fragment: &Fragment) -> Color {
     Color::new(128, 0, 128) // Purple color
 }
 
+#[allow(dead_code)]
 fn circle_shader(fragment: &Fragment) -> Color {
     let x = fragment.vertex_position.x;
     let y = fragment.vertex_position.y;
@@ -78,6 +100,7 @@ fn circle_shader(fragment: &Fragment) -> Color {
     }
 }
 
+#[allow(dead_code)]
 fn moving_circles_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let x = fragment.vertex_position.x;
     let y = fragment.vertex_position.y;
@@ -102,6 +125,7 @@ fn moving_circles_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     )
 }
 
+#[allow(dead_code)]
 pub fn combined_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let base_color = static_pattern_shader(fragment);
     let circle_color = moving_circles_shader(fragment, uniforms);
@@ -114,24 +138,19 @@ pub fn combined_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     }
 }
 
-pub fn combined_blend_shader(fragment: &Fragment, blend_mode: &str) -> Color {
+#[allow(dead_code)]
+pub fn combined_blend_shader(fragment: &Fragment, blend_mode: BlendMode) -> Color {
   
 
     let base_color = purple_shader(fragment);
     let circle_color = circle_shader(fragment);
   
-    let combined_color = match blend_mode {
-      "normal" => base_color.blend_normal(&circle_color),
-      "multiply" => base_color.blend_multiply(&circle_color),
-      "add" => base_color.blend_add(&circle_color),
-      "subtract" => base_color.blend_subtract(&circle_color),
-      _ => base_color
-    };
+    let combined_color = base_color.blend(&circle_color, blend_mode);
   
     combined_color * fragment.intensity
 }
 
-pub fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
   let x = fragment.vertex_position.x;
   let y = fragment.vertex_position.y;
 
@@ -161,37 +180,25 @@ pub fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
   );
 
 
-  let emission_intensity = 1.5; 
-  noise_color * emission_intensity
+  // En HDR el Sol puede ser mucho más brillante que los planetas
+  let emission_intensity = 4.0;
+  noise_color.to_linear() * emission_intensity
 }
 
-pub fn rocky_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-  let x = fragment.vertex_position.x;
-  let y = fragment.vertex_position.y;
-
-  let color_dark_gray = Color::new(105, 105, 105); 
-  let color_dark_brown = Color::new(80, 65, 55);   
+pub fn rocky_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
+  let direction = surface_direction(fragment);
 
+  let color_dark_gray = Color::new(105, 105, 105);
+  let color_dark_brown = Color::new(80, 65, 55);
 
-  let pattern = ((x * 10.0).sin() * (y * 10.0).cos()).abs();
-  let base_color = Color::new(
-      (color_dark_brown.get_r() as f32 * pattern) as u8,
-      (color_dark_brown.get_g() as f32 * pattern) as u8,
-      (color_dark_brown.get_b() as f32 * pattern) as u8,
-  );
-
-
-  let noise_intensity = (5.0 * (x * 5.0).sin() * (y * 5.0).cos()).abs();
-  let noise_color = Color::new(
-      (base_color.get_r() as f32 * noise_intensity) as u8,
-      (base_color.get_g() as f32 * noise_intensity) as u8,
-      (base_color.get_b() as f32 * noise_intensity) as u8,
-  );
+  // Manchas amplias de regolito más oscuro
+  let patches = fbm(&(direction * 3.0), 4, 0x3E5C);
+  let base_color = color_dark_gray.lerp(&color_dark_brown, smoothstep(0.45, 0.6, patches));
 
-  color_dark_gray.blend_multiply(&noise_color)
+  cratered(base_color.to_linear(), mercury_craters(), fragment, uniforms)
 }
 
-pub fn venus_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn venus_shader(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
   let x = fragment.vertex_position.x;
   let y = fragment.vertex_position.y;
 
@@ -207,13 +214,11 @@ pub fn venus_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
 
   // Mezcla de colores para simular las capas de nubes con ondas
   let base_color = color_soft_yellow.lerp(&color_light_gray, wave_pattern_x);
-  let final_color = base_color.lerp(&color_white, wave_pattern_y);
-
-  final_color
+  shadowed(base_color.lerp(&color_white, wave_pattern_y).to_linear(), fragment, uniforms)
 }
 
 
-pub fn earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
   let x = fragment.vertex_position.x;
   let y = fragment.vertex_position.y;
 
@@ -246,43 +251,170 @@ pub fn earth_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
   let cloud_y = y + cloud_time.cos() * 0.3;     // Desplazamiento en y para las nubes
   let cloud_noise = ((cloud_x * 20.0).sin() * (cloud_y * 20.0).cos()).abs();
 
-  let cloud_color = if cloud_noise > 0.6 {
+  let is_cloud = cloud_noise > 0.6;
+  let is_water = continent_factor <= 0.4;
+
+  let cloud_color = if is_cloud {
       color_cloud
   } else {
       base_color
   };
 
-  cloud_color
+  // Iluminación con el Sol como fuente de luz, en espacio de mundo
+  let p = fragment.vertex_position;
+  let world_position = world_position(fragment, uniforms);
+  let normal = fragment.normal.normalize();
+  let light_dir = (uniforms.light_position - world_position).normalize();
+  let view_dir = (uniforms.camera_position - world_position).normalize();
+  let n_dot_l = normal.dot(&light_dir);
+
+  // Terminador suave: el día se desvanece a lo largo de una franja en lugar de cortarse.
+  // Durante un eclipse la sombra de la Luna apaga la luz directa y enciende las ciudades.
+  let sun_visibility = uniforms.shadow_casters.visibility(&world_position, &uniforms.light_position);
+  let daylight = smoothstep(-0.12, 0.18, n_dot_l) * sun_visibility;
+  let twilight = (1.0 - (n_dot_l / 0.2).abs()).clamp(0.0, 1.0);
+  let twilight_tint = LinearColor::new(1.0, 1.0, 1.0).lerp(&LinearColor::new(1.0, 0.45, 0.2), twilight * 0.6);
+
+  let ambient = 0.015;
+  let diffuse = n_dot_l.max(0.0);
+  let day_color = cloud_color.to_linear() * twilight_tint * (diffuse + ambient);
+
+  // Brillo especular de Blinn-Phong sólo sobre el océano descubierto
+  let specular = if is_water && !is_cloud && n_dot_l > 0.0 {
+      let half_dir = (light_dir + view_dir).normalize();
+      normal.dot(&half_dir).max(0.0).powf(60.0) * 1.6
+  } else {
+      0.0
+  };
+  let glint = LinearColor::new(1.0, 0.95, 0.85) * (specular * daylight);
+
+  // Luces de ciudades en tierra firme del lado nocturno, atenuadas por las nubes
+  let night_lights = if is_water {
+      0.0
+  } else {
+      let surface_point = Vec3::new(moving_x, moving_y, p.z);
+      let clusters = smoothstep(0.58, 0.72, fbm(&(surface_point * 9.0), 3, 0xC17E));
+      let towns = if value_noise(&(surface_point * 55.0), 0x70A1) > 0.78 { 0.35 } else { 0.0 };
+      let cover = if is_cloud { 0.25 } else { 1.0 };
+      (clusters + towns) * cover
+  };
+  let city_color = Color::new(255, 196, 120).to_linear() * (night_lights * 0.8 * (1.0 - daylight));
+
+  day_color * daylight + glint + city_color
 }
 
+fn world_position(fragment: &Fragment, uniforms: &Uniforms) -> Vec3 {
+  let p = fragment.vertex_position;
+  let world = uniforms.model_matrix * Vec4::new(p.x, p.y, p.z, 1.0);
+  Vec3::new(world.x, world.y, world.z)
+}
 
+// Atenúa el color según la parte del Sol que tapan otros cuerpos (umbra y penumbra)
+fn shadowed(color: LinearColor, fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
+  let visibility = uniforms.shadow_casters.visibility(&world_position(fragment, uniforms), &uniforms.light_position);
+  color * (SHADOW_AMBIENT + (1.0 - SHADOW_AMBIENT) * visibility)
+}
 
+// Luz difusa del Sol según `normal` (en espacio de mundo), así se ven las pendientes del relieve
+fn sunlit(color: LinearColor, normal: &Vec3, fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
+  let light_dir = (uniforms.light_position - world_position(fragment, uniforms)).normalize();
+  let diffuse = normal.dot(&light_dir).max(0.0);
+  shadowed(color * (NIGHT_AMBIENT + (1.0 - NIGHT_AMBIENT) * diffuse), fragment, uniforms)
+}
 
-pub fn mars_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-  let x = fragment.vertex_position.x;
-  let y = fragment.vertex_position.y;
+// Dirección desde el centro del cuerpo en espacio de objeto
+fn surface_direction(fragment: &Fragment) -> Vec3 {
+  fragment.vertex_position.try_normalize(f32::EPSILON).unwrap_or(Vec3::new(0.0, 1.0, 0.0))
+}
+
+// Superficie con cráteres: los fondos se oscurecen y el material eyectado y los rayos la
+// aclaran. La pendiente de los cráteres se calcula en cada fragmento sobre su campo de alturas
+// (bump mapping) y se suma a la normal interpolada, que con relieve en el vertex shader trae
+// la del ruido y sin él es la de la esfera.
+fn cratered(color: LinearColor, craters: &CraterField, fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
+  let direction = surface_direction(fragment);
+  let sample = craters.sample(&direction);
+  let albedo = color * (1.0 - 0.3 * sample.floor) * (1.0 + 0.9 * sample.ejecta);
+
+  // La matriz de modelo sólo escala de forma uniforme, así que sirve para las normales
+  let model = mat4_to_mat3(&uniforms.model_matrix);
+  let scale = model.column(0).magnitude();
+  let bumped = height_field_normal(&direction, sample.height, |direction| craters.height(direction));
+  let tilt = model * (bumped - direction) / scale;
+  let normal = (fragment.normal.normalize() + tilt).normalize();
+
+  sunlit(albedo, &normal, fragment, uniforms)
+}
+
+// Cráteres de cada cuerpo rocoso. Se generan la primera vez que se piden y los usan tanto el
+// fragment shader como el relieve del vertex shader.
+pub fn mercury_craters() -> &'static CraterField {
+  static FIELD: OnceLock<CraterField> = OnceLock::new();
+  FIELD.get_or_init(|| CraterField::generate(CraterParams {
+    count: 900,
+    min_radius: 0.015,
+    max_radius: 0.3,
+    size_exponent: 1.6,
+    depth: 0.12,
+    rim_height: 0.35,
+    ejecta_reach: 2.2,
+    ray_fraction: 0.06,
+    seed: 0x3E5C,
+  }))
+}
 
+pub fn moon_craters() -> &'static CraterField {
+  static FIELD: OnceLock<CraterField> = OnceLock::new();
+  FIELD.get_or_init(|| CraterField::generate(CraterParams {
+    count: 700,
+    min_radius: 0.015,
+    max_radius: 0.35,
+    size_exponent: 1.7,
+    depth: 0.12,
+    rim_height: 0.3,
+    ejecta_reach: 2.0,
+    ray_fraction: 0.05,
+    seed: 0x4D0A,
+  }))
+}
 
-  let color_red = Color::new(205, 92, 92);       
-  let color_dark_red = Color::new(139, 69, 19);  
-  let color_brown = Color::new(165, 42, 42);     
+// Marte tiene menos cráteres y más erosionados: más bajos, sin rayos y con poco manto
+pub fn mars_craters() -> &'static CraterField {
+  static FIELD: OnceLock<CraterField> = OnceLock::new();
+  FIELD.get_or_init(|| CraterField::generate(CraterParams {
+    count: 250,
+    min_radius: 0.02,
+    max_radius: 0.25,
+    size_exponent: 2.0,
+    depth: 0.07,
+    rim_height: 0.2,
+    ejecta_reach: 1.7,
+    ray_fraction: 0.0,
+    seed: 0x3A75,
+  }))
+}
 
 
-  let surface_noise = ((x * 8.0).sin() * (y * 8.0).cos()).abs();
-  let crater_noise = ((x * 15.0).sin() * (y * 15.0).cos()).abs();
 
-  let base_color = if crater_noise > 0.6 {
-      color_dark_red 
-  } else if surface_noise > 0.4 {
-      color_brown 
-  } else {
-      color_red 
-  };
 
-  base_color
+pub fn mars_shader(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
+  let direction = surface_direction(fragment);
+
+  let color_red = Color::new(205, 92, 92);
+  let color_dark_red = Color::new(139, 69, 19);
+  let color_brown = Color::new(165, 42, 42);
+
+  // Polvo rojizo con zonas más oscuras de roca expuesta
+  let dust = fbm(&(direction * 2.5), 5, 0x3A75);
+  let rock = fbm(&(direction * 1.2), 3, 0x3A76);
+  let base_color = color_red
+    .lerp(&color_brown, smoothstep(0.45, 0.6, dust))
+    .lerp(&color_dark_red, smoothstep(0.52, 0.64, rock) * 0.7);
+
+  cratered(base_color.to_linear(), mars_craters(), fragment, uniforms)
 }
 
-pub fn jupiter_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn jupiter_shader(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
   let x = fragment.vertex_position.x;
   let y = fragment.vertex_position.y;
 
@@ -315,29 +447,23 @@ pub fn jupiter_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
       base_color
   };
 
-  final_color
+  shadowed(final_color.to_linear(), fragment, uniforms)
 }
 
-pub fn moon_shader(fragment: &Fragment, _uniforms: &Uniforms) -> Color {
-  let x = fragment.vertex_position.x;
-  let y = fragment.vertex_position.y;
-
+pub fn moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
+  let direction = surface_direction(fragment);
 
-  let color_light_gray = Color::new(200, 200, 200); 
-  let color_dark_gray = Color::new(105, 105, 105); 
+  let color_light_gray = Color::new(200, 200, 200);
+  let color_dark_gray = Color::new(105, 105, 105);
 
-  let surface_noise = ((x * 5.0).sin() * (y * 5.0).cos()).abs();
-  let base_color = if surface_noise > 0.5 {
-      color_dark_gray
-  } else {
-      color_light_gray
-  };
+  // Mares: llanuras de basalto oscuro en las zonas bajas del ruido
+  let maria = 1.0 - smoothstep(0.4, 0.48, fbm(&(direction * 1.6), 4, 0x4D0A));
+  let base_color = color_light_gray.lerp(&color_dark_gray, maria);
 
-  base_color
+  cratered(base_color.to_linear(), moon_craters(), fragment, uniforms)
 }
 
-pub fn saturn_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-  let x = fragment.vertex_position.x;
+pub fn saturn_shader(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
   let y = fragment.vertex_position.y;
 
   let color_pale_yellow = Color::new(253, 253, 150); 
@@ -355,36 +481,77 @@ pub fn saturn_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
       color_light_brown
   };
 
-  base_color
+  shadowed(base_color.to_linear(), fragment, uniforms)
 }
 
-pub fn saturn_rings_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+pub fn saturn_rings_shader(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
   let x = fragment.vertex_position.x;
-  let y = fragment.vertex_position.y;
+  let z = fragment.vertex_position.z;
 
-  // Distancia radial desde el centro para crear anillos concéntricos
-  let distance = (x * x + y * y).sqrt();
+  // Distancia radial desde el centro, en el plano de los anillos
+  let distance = (x * x + z * z).sqrt();
 
   // Colores para los anillos
   let color_ring1 = Color::new(200, 200, 200); // Gris claro
   let color_ring2 = Color::new(169, 169, 169); // Gris oscuro
 
-  // Crear anillos concéntricos basados en la distancia
-  if distance > 1.1 && distance < 1.2 {
-      color_ring1
-  } else if distance > 1.3 && distance < 1.4 {
-      color_ring2
-  } else if distance > 1.5 && distance < 1.6 {
+  // Las bandas alternan de color; fuera de ellas no hay geometría
+  let band = RING_BANDS.iter().rposition(|&(inner, _)| distance >= inner).unwrap_or(0);
+  let ring_color = if band % 2 == 0 {
       color_ring1
-  } else if distance > 1.7 && distance < 1.8 {
-      color_ring2
   } else {
-      Color::new(0, 0, 0) // Negro (transparente) para el área sin anillos
-  }
+      color_ring2
+  };
+
+  // El planeta proyecta su sombra sobre el plano de los anillos
+  shadowed(ring_color.to_linear(), fragment, uniforms)
 }
 
+pub fn uranus_shader(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
+  let y = fragment.vertex_position.y;
 
+  let color_cyan = Color::new(150, 210, 220);
+  let color_pale_cyan = Color::new(185, 230, 235);
 
+  // Bandas muy tenues: la bruma de metano casi no deja ver la estructura de las nubes
+  let band_pattern = ((y * 4.0 + uniforms.time as f32 * 0.005).sin() * 0.5 + 0.5).clamp(0.0, 1.0);
+  let base_color = color_cyan.lerp(&color_pale_cyan, band_pattern * 0.5);
 
+  shadowed(base_color.to_linear(), fragment, uniforms)
+}
 
+pub fn neptune_shader(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
+  let x = fragment.vertex_position.x;
+  let y = fragment.vertex_position.y;
 
+  let color_blue = Color::new(60, 100, 220);
+  let color_deep_blue = Color::new(35, 60, 170);
+  let color_dark_spot = Color::new(20, 30, 100);
+  let color_cloud = Color::new(235, 240, 255);
+
+  let time = uniforms.time as f32 * 0.015;
+  let band_pattern = ((y * 8.0 + time).sin() * 0.5 + 0.5).clamp(0.0, 1.0);
+  let base_color = color_blue.lerp(&color_deep_blue, band_pattern);
+
+  // Gran Mancha Oscura con nubes altas brillantes en su borde sur
+  let spot_x = (x + 0.25).powi(2) / 0.06;
+  let spot_y = (y - 0.3).powi(2) / 0.02;
+  let spot_intensity = 1.0 - (spot_x + spot_y).clamp(0.0, 1.0);
+  let cloud_y = (y - 0.18).powi(2) / 0.002;
+  let cloud_intensity = 1.0 - (spot_x + cloud_y).clamp(0.0, 1.0);
+
+  let final_color = if spot_intensity > 0.3 {
+      base_color.lerp(&color_dark_spot, spot_intensity)
+  } else if cloud_intensity > 0.5 {
+      base_color.lerp(&color_cloud, cloud_intensity)
+  } else {
+      base_color
+  };
+
+  shadowed(final_color.to_linear(), fragment, uniforms)
+}
+
+// Modelos glTF: el color base de cada material llega en el color de los vértices
+pub fn station_shader(fragment: &Fragment, uniforms: &Uniforms) -> LinearColor {
+  sunlit(fragment.color.to_linear(), &fragment.normal, fragment, uniforms)
+}
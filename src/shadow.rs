@@ -0,0 +1,99 @@
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+use crate::culling::BoundingSphere;
+
+// Cuerpos que pueden proyectar sombra en un frame (todos los planetas y la Luna)
+pub const MAX_SHADOW_CASTERS: usize = 8;
+
+// Esferas en espacio de mundo que ocultan al Sol. Las sombras se calculan de forma
+// analítica: la fracción visible del disco solar desde un punto es el área del disco que
+// no tapa el disco de cada cuerpo, lo que da umbra (nada visible), penumbra (una parte) y
+// antumbra (un anillo del Sol alrededor del cuerpo). Es `Copy` para viajar en `Uniforms`.
+#[derive(Debug, Clone, Copy)]
+pub struct ShadowCasters {
+    casters: [BoundingSphere; MAX_SHADOW_CASTERS],
+    count: usize,
+    light_radius: f32,
+}
+
+impl ShadowCasters {
+    pub fn new(light_radius: f32) -> Self {
+        ShadowCasters {
+            casters: [BoundingSphere { center: Vec3::zeros(), radius: 0.0 }; MAX_SHADOW_CASTERS],
+            count: 0,
+            light_radius,
+        }
+    }
+
+    // Sin cuerpos: todo recibe la luz completa
+    pub fn none() -> Self {
+        ShadowCasters::new(0.0)
+    }
+
+    pub fn add(&mut self, caster: BoundingSphere) {
+        assert!(self.count < MAX_SHADOW_CASTERS, "demasiados cuerpos que proyectan sombra");
+        self.casters[self.count] = caster;
+        self.count += 1;
+    }
+
+    // Copia sin `caster`, para que un cuerpo no se sombree a sí mismo (de eso ya se
+    // encarga el término difuso del shader)
+    pub fn without(&self, caster: &BoundingSphere) -> Self {
+        let mut result = ShadowCasters::new(self.light_radius);
+        for other in self.casters[..self.count].iter().filter(|other| *other != caster) {
+            result.add(*other);
+        }
+        result
+    }
+
+    // Fracción del disco solar visible desde `point`, entre 0 (umbra) y 1 (pleno Sol).
+    // Varias sombras superpuestas se combinan multiplicando.
+    pub fn visibility(&self, point: &Vec3, light_position: &Vec3) -> f32 {
+        let to_light = light_position - point;
+        let light_distance = to_light.magnitude();
+        if light_distance <= self.light_radius {
+            return 1.0;
+        }
+        let to_light = to_light / light_distance;
+        let light_angle = (self.light_radius / light_distance).asin();
+
+        let mut visibility = 1.0;
+        for caster in &self.casters[..self.count] {
+            let to_caster = caster.center - point;
+            let distance = to_caster.magnitude();
+
+            // Cuerpos detrás del Sol o que contienen al punto no le hacen sombra
+            if distance <= caster.radius || distance >= light_distance {
+                continue;
+            }
+
+            let caster_angle = (caster.radius / distance).asin();
+            let separation = (to_caster / distance).dot(&to_light).clamp(-1.0, 1.0).acos();
+            if separation >= light_angle + caster_angle {
+                continue;
+            }
+
+            let covered = disc_overlap(light_angle, caster_angle, separation) / (PI * light_angle * light_angle);
+            visibility *= 1.0 - covered.clamp(0.0, 1.0);
+        }
+        visibility
+    }
+}
+
+// Área de la intersección de dos discos de radios `r1` y `r2` con centros a distancia `d`.
+// Con radios angulares pequeños es una buena aproximación del solapamiento en el cielo.
+fn disc_overlap(r1: f32, r2: f32, d: f32) -> f32 {
+    if d >= r1 + r2 {
+        return 0.0;
+    }
+    if d <= (r1 - r2).abs() {
+        let r = r1.min(r2);
+        return PI * r * r;
+    }
+
+    let a1 = ((d * d + r1 * r1 - r2 * r2) / (2.0 * d * r1)).clamp(-1.0, 1.0).acos();
+    let a2 = ((d * d + r2 * r2 - r1 * r1) / (2.0 * d * r2)).clamp(-1.0, 1.0).acos();
+    let kite = ((-d + r1 + r2) * (d + r1 - r2) * (d - r1 + r2) * (d + r1 + r2)).max(0.0).sqrt();
+
+    r1 * r1 * a1 + r2 * r2 * a2 - 0.5 * kite
+}
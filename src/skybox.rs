@@ -0,0 +1,189 @@
+use nalgebra_glm::{Vec3, Vec4};
+use std::f32::consts::PI;
+use crate::color::{Color, LinearColor};
+use crate::framebuffer::Framebuffer;
+use crate::noise::{fbm, Random};
+use crate::Uniforms;
+
+const BAND_WIDTH: usize = 1024;
+const BAND_HEIGHT: usize = 512;
+
+struct Star {
+    direction: Vec3,
+    color: LinearColor,
+    brightness: f32,
+}
+
+// Fondo de estrellas y Vía Láctea sobre una esfera celeste centrada en la cámara.
+// Todo se genera a partir de `seed`, así que el cielo es el mismo en cada ejecución.
+pub struct Skybox {
+    stars: Vec<Star>,
+    band: Vec<LinearColor>, // Vía Láctea precalculada en proyección equirectangular
+}
+
+impl Skybox {
+    pub fn new(star_count: usize, seed: u32) -> Self {
+        let galactic_pole = ecliptic_direction(180.02, 29.81);
+        let galactic_center = ecliptic_direction(266.84, -5.54);
+
+        let mut random = Random::new(seed);
+        let mut stars = Vec::with_capacity(star_count);
+
+        while stars.len() < star_count {
+            let direction = random.unit_vector();
+
+            // Más estrellas cerca del plano galáctico
+            let latitude = direction.dot(&galactic_pole).asin();
+            let density = 0.35 + 0.65 * (-(latitude / 0.3).powi(2)).exp();
+            if random.next_f32() > density {
+                continue;
+            }
+
+            // Magnitud aparente con N(<m) ∝ 10^(0.6 m): pocas estrellas brillantes, muchas débiles
+            let magnitude = (6.5 + random.next_f32().max(1e-6).log10() / 0.6).max(-1.5);
+            let brightness = 10f32.powf(-0.4 * (magnitude - 4.0));
+
+            // Las estrellas frías son mucho más comunes que las calientes
+            let temperature = 2800.0 + 28000.0 * random.next_f32().powi(4);
+
+            stars.push(Star {
+                direction,
+                color: temperature_to_color(temperature).to_linear(),
+                brightness,
+            });
+        }
+
+        let band = (0..BAND_WIDTH * BAND_HEIGHT)
+            .map(|i| {
+                let longitude = ((i % BAND_WIDTH) as f32 + 0.5) / BAND_WIDTH as f32 * 2.0 * PI - PI;
+                let latitude = PI / 2.0 - ((i / BAND_WIDTH) as f32 + 0.5) / BAND_HEIGHT as f32 * PI;
+                let direction = Vec3::new(
+                    latitude.cos() * longitude.cos(),
+                    latitude.sin(),
+                    latitude.cos() * longitude.sin(),
+                );
+                milky_way_color(&direction, &galactic_pole, &galactic_center, seed)
+            })
+            .collect();
+
+        Skybox { stars, band }
+    }
+
+    // Dibuja el cielo en el buffer HDR. Sólo se usa la rotación de la matriz de vista,
+    // de modo que el fondo gira con la cámara pero nunca se acerca ni se aleja.
+    pub fn render(&self, framebuffer: &mut Framebuffer, uniforms: &Uniforms) {
+        let mut rotation = uniforms.view_matrix;
+        rotation[(0, 3)] = 0.0;
+        rotation[(1, 3)] = 0.0;
+        rotation[(2, 3)] = 0.0;
+
+        let sky_to_screen = uniforms.viewport_matrix * uniforms.projection_matrix * rotation;
+        let screen_to_sky = match sky_to_screen.try_inverse() {
+            Some(inverse) => inverse,
+            None => return,
+        };
+
+        // En el plano far la dirección de vista varía linealmente con el píxel
+        let unproject = |x: f32, y: f32| {
+            let p = screen_to_sky * Vec4::new(x, y, 1.0, 1.0);
+            Vec3::new(p.x, p.y, p.z) / p.w
+        };
+        let origin = unproject(0.5, 0.5);
+        let step_x = unproject(1.5, 0.5) - origin;
+        let step_y = unproject(0.5, 1.5) - origin;
+
+        for y in 0..framebuffer.height {
+            let row = origin + step_y * y as f32;
+            for x in 0..framebuffer.width {
+                let direction = (row + step_x * x as f32).normalize();
+                framebuffer.set_pixel(x, y, self.sample_band(&direction));
+            }
+        }
+
+        for star in &self.stars {
+            let clip = sky_to_screen * Vec4::new(star.direction.x, star.direction.y, star.direction.z, 1.0);
+            if clip.w <= 0.0 {
+                continue;
+            }
+
+            let x = (clip.x / clip.w).floor() as i32;
+            let y = (clip.y / clip.w).floor() as i32;
+
+            // Las estrellas brillantes se extienden un poco a los píxeles vecinos
+            add_light(framebuffer, x, y, &star.color, star.brightness.min(1.5));
+            if star.brightness > 1.0 {
+                let halo = ((star.brightness - 1.0) * 0.25).min(0.6);
+                for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                    add_light(framebuffer, x + dx, y + dy, &star.color, halo);
+                }
+            }
+        }
+    }
+
+    fn sample_band(&self, direction: &Vec3) -> LinearColor {
+        let longitude = direction.z.atan2(direction.x);
+        let latitude = direction.y.clamp(-1.0, 1.0).asin();
+
+        let u = ((longitude + PI) / (2.0 * PI) * BAND_WIDTH as f32) as usize;
+        let v = ((PI / 2.0 - latitude) / PI * BAND_HEIGHT as f32) as usize;
+
+        self.band[v.min(BAND_HEIGHT - 1) * BAND_WIDTH + u.min(BAND_WIDTH - 1)]
+    }
+}
+
+fn add_light(framebuffer: &mut Framebuffer, x: i32, y: i32, color: &LinearColor, intensity: f32) {
+    if x < 0 || y < 0 || x as usize >= framebuffer.width || y as usize >= framebuffer.height {
+        return;
+    }
+    framebuffer.add_pixel(x as usize, y as usize, *color * intensity);
+}
+
+// Coordenadas eclípticas (grados) a dirección en la escena, con la misma convención
+// que `Orbit`: la eclíptica es el plano XZ y su polo norte apunta a +Y.
+fn ecliptic_direction(longitude: f32, latitude: f32) -> Vec3 {
+    let (lon, lat) = (longitude.to_radians(), latitude.to_radians());
+    Vec3::new(lat.cos() * lon.cos(), lat.sin(), -lat.cos() * lon.sin())
+}
+
+fn milky_way_color(direction: &Vec3, galactic_pole: &Vec3, galactic_center: &Vec3, seed: u32) -> LinearColor {
+    let latitude = direction.dot(galactic_pole).asin();
+    let core = direction.dot(galactic_center).max(0.0);
+
+    // Banda gaussiana en latitud galáctica, más ancha y brillante hacia el bulbo central
+    let width = 0.16 + 0.12 * core.powi(4);
+    let density = (-(latitude / width).powi(2)).exp();
+
+    let detail = fbm(&(direction * 6.0), 4, seed);
+    let dust = 1.0 - 0.7 * (-(latitude / 0.05).powi(2)).exp() * fbm(&(direction * 14.0), 3, seed ^ 0xD05);
+
+    let intensity = density * (0.3 + 0.7 * detail) * dust * (0.45 + 1.2 * core.powi(3));
+
+    let color_core = Color::new(255, 236, 205);
+    let color_arm = Color::new(190, 205, 255);
+    color_arm.lerp(&color_core, core).to_linear() * (intensity * 0.07)
+}
+
+// Aproximación del color de un cuerpo negro (Tanner Helland) para 1000 K - 40000 K
+pub fn temperature_to_color(kelvin: f32) -> Color {
+    let t = kelvin / 100.0;
+
+    let r = if t <= 66.0 {
+        255.0
+    } else {
+        329.698_73 * (t - 60.0).powf(-0.133_204_76)
+    };
+    let g = if t <= 66.0 {
+        99.470_8 * t.ln() - 161.119_57
+    } else {
+        288.122_16 * (t - 60.0).powf(-0.075_514_85)
+    };
+    let b = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.517_73 * (t - 10.0).ln() - 305.044_8
+    };
+
+    Color::new(r.clamp(0.0, 255.0) as u8, g.clamp(0.0, 255.0) as u8, b.clamp(0.0, 255.0) as u8)
+}
@@ -0,0 +1,75 @@
+use nalgebra_glm::Vec3;
+use crate::craters::CraterField;
+use crate::noise::fbm;
+
+// Paso angular (en radianes, aproximado) de las diferencias finitas con que se recalcula la
+// normal del relieve
+const NORMAL_STEP: f32 = 2e-3;
+
+// Relieve procedural de un cuerpo rocoso: el vertex shader mueve cada vértice a lo largo de
+// la dirección desde el centro según un campo de alturas de ruido. Las alturas están en
+// fracción del radio, así que los mismos valores sirven a cualquier escala; la malla debe
+// estar centrada en el origen y ser lo bastante densa para que el relieve se vea en la silueta.
+#[derive(Debug, Clone, Copy)]
+pub struct Terrain {
+    pub amplitude: f32, // desnivel máximo respecto del radio original, hacia arriba y hacia abajo
+    pub frequency: f32, // escala de la primera octava sobre la esfera unitaria
+    pub octaves: u32,
+    pub ridges: f32,    // 0 da colinas redondeadas y 1 crestas afiladas con valles anchos
+    pub seed: u32,
+    pub craters: Option<&'static CraterField>, // cráteres que se suman al ruido en la posición
+}
+
+impl Terrain {
+    // Altura en fracción del radio en la dirección unitaria `direction`, con los cráteres
+    pub fn height(&self, direction: &Vec3) -> f32 {
+        self.noise_height(direction) + self.craters.map_or(0.0, |craters| craters.height(direction))
+    }
+
+    // Parte de ruido de la altura, en [-amplitude, amplitude]
+    pub fn noise_height(&self, direction: &Vec3) -> f32 {
+        let noise = fbm(&(direction * self.frequency), self.octaves, self.seed) * 2.0 - 1.0;
+        // Ruido "ridged": el valor absoluto pliega el ruido y deja crestas donde cruzaba el cero
+        let ridged = 1.0 - 2.0 * noise.abs();
+        (noise + (ridged - noise) * self.ridges) * self.amplitude
+    }
+
+    // Cuánto puede sobresalir el relieve del radio original
+    pub fn max_height(&self) -> f32 {
+        self.amplitude + self.craters.map_or(0.0, |craters| craters.max_height())
+    }
+
+    // Posición desplazada y normal del relieve para un vértice de la malla original. Los
+    // cráteres mueven la posición (se ven en la silueta) pero la normal sólo sigue el ruido:
+    // los shaders de los cuerpos con cráteres agregan su pendiente en cada fragmento, con
+    // más detalle del que permite la malla.
+    pub fn displace(&self, position: &Vec3) -> (Vec3, Vec3) {
+        let radius = position.magnitude();
+        let Some(direction) = position.try_normalize(f32::EPSILON) else {
+            return (*position, Vec3::new(0.0, 1.0, 0.0));
+        };
+
+        let noise_height = self.noise_height(&direction);
+        let normal = height_field_normal(&direction, noise_height, |direction| self.noise_height(direction));
+        (direction * radius * (1.0 + self.height(&direction)), normal)
+    }
+}
+
+// Normal de la superficie r = 1 + height(dirección) en la dirección unitaria `direction`, por
+// diferencias hacia adelante sobre dos tangentes. `center_height` es la altura en `direction`,
+// que quien llama ya calculó, así cada normal cuesta sólo dos evaluaciones más.
+pub fn height_field_normal(direction: &Vec3, center_height: f32, height: impl Fn(&Vec3) -> f32) -> Vec3 {
+    let surface = |direction: Vec3| {
+        let direction = direction.normalize();
+        direction * (1.0 + height(&direction))
+    };
+
+    let helper = if direction.y.abs() < 0.9 { Vec3::new(0.0, 1.0, 0.0) } else { Vec3::new(1.0, 0.0, 0.0) };
+    let tangent = direction.cross(&helper).normalize();
+    let bitangent = direction.cross(&tangent);
+
+    let center = direction * (1.0 + center_height);
+    let du = surface(direction + tangent * NORMAL_STEP) - center;
+    let dv = surface(direction + bitangent * NORMAL_STEP) - center;
+    du.cross(&dv).try_normalize(f32::EPSILON).unwrap_or(*direction)
+}
@@ -0,0 +1,58 @@
+use nalgebra_glm::Vec3;
+
+// Estela de posiciones pasadas de un cuerpo en un buffer circular de tamaño fijo: al
+// llenarse, cada punto nuevo reemplaza al más viejo. Sólo se guarda un punto cuando el
+// cuerpo se alejó `spacing` del anterior, así los cuerpos lentos y los rápidos dejan estelas
+// de largo parecido en pantalla.
+pub struct Trail {
+    points: Vec<Vec3>,
+    capacity: usize,
+    next: usize, // posición donde se escribe el próximo punto
+    spacing: f32,
+}
+
+impl Trail {
+    pub fn new(capacity: usize, spacing: f32) -> Self {
+        Trail { points: Vec::with_capacity(capacity), capacity: capacity.max(1), next: 0, spacing }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+        self.next = 0;
+    }
+
+    fn last(&self) -> Option<&Vec3> {
+        if self.points.is_empty() {
+            return None;
+        }
+        Some(&self.points[(self.next + self.capacity - 1) % self.capacity])
+    }
+
+    // Agrega la posición actual si está a más de `spacing` del último punto guardado
+    pub fn push(&mut self, position: Vec3) {
+        if self.last().is_some_and(|last| (position - last).magnitude() < self.spacing) {
+            return;
+        }
+
+        if self.points.len() < self.capacity {
+            self.points.push(position);
+        } else {
+            self.points[self.next] = position;
+        }
+        self.next = (self.next + 1) % self.capacity;
+    }
+
+    // Puntos del más viejo al más nuevo
+    pub fn points(&self) -> impl Iterator<Item = &Vec3> {
+        let start = if self.points.len() < self.capacity { 0 } else { self.next };
+        self.points[start..].iter().chain(self.points[..start].iter())
+    }
+}
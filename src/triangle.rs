@@ -1,9 +1,12 @@
 use nalgebra_glm::{Vec3, dot};
 use crate::fragment::Fragment;
 use crate::vertex::Vertex;
-use crate::color::Color;
+use crate::framebuffer::MAX_SAMPLES;
 
-pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
+// `sample_pattern` son las posiciones de muestreo dentro de cada píxel. La cobertura y la
+// profundidad se evalúan por muestra, pero el resto de atributos se interpola una sola vez
+// en el centro del píxel, así que el fragment shader corre una vez por píxel (MSAA).
+pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex, sample_pattern: &[(f32, f32)]) -> Vec<Fragment> {
   let mut fragments = Vec::new();
   let (a, b, c) = (v1.transformed_position, v2.transformed_position, v3.transformed_position);
 
@@ -15,44 +18,64 @@ pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
 
   for y in min_y..=max_y {
     for x in min_x..=max_x {
-      let point = Vec3::new(x as f32 + 0.5, y as f32 + 0.5, 0.0);
+      let mut coverage = 0u8;
+      let mut sample_depths = [f32::INFINITY; MAX_SAMPLES];
+
+      for (sample, (sx, sy)) in sample_pattern.iter().enumerate().take(MAX_SAMPLES) {
+        let point = Vec3::new(x as f32 + sx, y as f32 + sy, 0.0);
+        let (w1, w2, w3) = barycentric_coordinates(&point, &a, &b, &c, triangle_area);
+
+        if (0.0..=1.0).contains(&w1) &&
+           (0.0..=1.0).contains(&w2) &&
+           (0.0..=1.0).contains(&w3) {
+          coverage |= 1 << sample;
+          sample_depths[sample] = a.z * w1 + b.z * w2 + c.z * w3;
+        }
+      }
 
-      let (w1, w2, w3) = barycentric_coordinates(&point, &a, &b, &c, triangle_area);
+      if coverage == 0 {
+        continue;
+      }
 
-      if w1 >= 0.0 && w1 <= 1.0 && 
-         w2 >= 0.0 && w2 <= 1.0 &&
-         w3 >= 0.0 && w3 <= 1.0 {
+      // Con una sola muestra el punto de evaluación es esa misma muestra (el centro)
+      let point = Vec3::new(x as f32 + 0.5, y as f32 + 0.5, 0.0);
+      let (w1, w2, w3) = barycentric_coordinates(&point, &a, &b, &c, triangle_area);
 
-        let normal = v1.transformed_normal * w1 + v2.transformed_normal * w2 + v3.transformed_normal * w3;
-        let normal = normal.normalize();
+      let normal = v1.transformed_normal * w1 + v2.transformed_normal * w2 + v3.transformed_normal * w3;
+      let normal = normal.normalize();
 
-        let intensity = dot(&normal, &light_dir).max(0.0);
+      let intensity = dot(&normal, &light_dir).max(0.0);
 
-        let base_color = Color::new(100, 100, 100);
-        let lit_color = base_color * intensity;
+      // Color de los vértices interpolado en espacio lineal (el color base de los modelos glTF)
+      let color = (v1.color.to_linear() * w1 + v2.color.to_linear() * w2 + v3.color.to_linear() * w3).to_srgb();
 
-        let depth = a.z * w1 + b.z * w2 + c.z * w3;
+      let depth = a.z * w1 + b.z * w2 + c.z * w3;
 
-        let vertex_position = v1.position * w1 + v2.position * w2 + v3.position * w3;
+      let vertex_position = v1.position * w1 + v2.position * w2 + v3.position * w3;
 
-        fragments.push(
-            Fragment::new(
-                x as f32,
-                y as f32,
-                lit_color,
-                depth,
-                normal,
-                intensity,
-                vertex_position,
-            )
-        );
-      }
+      let mut fragment = Fragment::new(
+          x as f32,
+          y as f32,
+          color,
+          depth,
+          normal,
+          intensity,
+          vertex_position,
+      );
+      fragment.coverage = coverage;
+      fragment.sample_depths = sample_depths;
+      fragments.push(fragment);
     }
   }
 
   fragments
 }
 
+// Caras en orden antihorario (convención OBJ) quedan con área positiva en pantalla
+pub fn is_front_facing(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> bool {
+    edge_function(&v1.transformed_position, &v2.transformed_position, &v3.transformed_position) > 0.0
+}
+
 fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i32) {
     let min_x = v1.x.min(v2.x).min(v3.x).floor() as i32;
     let min_y = v1.y.min(v2.y).min(v3.y).floor() as i32;